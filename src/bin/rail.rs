@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use rail_lang::v1::{
     loading, log, RunConventions, RAIL_ERROR_PREFIX, RAIL_FATAL_PREFIX, RAIL_INFO_PREFIX,
     RAIL_WARN_PREFIX,
@@ -14,14 +14,27 @@ const CONV: RunConventions = RunConventions {
     warn_prefix: RAIL_WARN_PREFIX,
     error_prefix: RAIL_ERROR_PREFIX,
     fatal_prefix: RAIL_FATAL_PREFIX,
+    deny_redefine: false,
+};
+
+const STRICT_CONV: RunConventions = RunConventions {
+    deny_redefine: true,
+    ..CONV
 };
 
 pub fn main() {
     let args = RailEvaluator::parse();
 
-    let state = match loading::initial_rail_state(args.no_stdlib, args.lib_list, &CONV) {
+    let conventions = if args.deny_redefine {
+        &STRICT_CONV
+    } else {
+        &CONV
+    };
+
+    let state = match loading::initial_rail_state(args.no_stdlib, args.lib_list, conventions) {
         Ok(state) => state,
-        Err((state, err)) => {
+        Err(failure) => {
+            let (state, err) = *failure;
             log::fatal(
                 &CONV,
                 format!(
@@ -33,9 +46,24 @@ pub fn main() {
         }
     };
 
-    let tokens = loading::get_source_as_tokens(args.rail_code.join(" "));
+    if let Some(seed) = args.seed {
+        state.rng.reseed(seed as u64);
+    }
+
+    let dialect = match args.dialect {
+        Dialect::Rail => loading::Dialect::Rail,
+        Dialect::Forth => loading::Dialect::Forth,
+    };
+
+    let nodes = match loading::parse_dialect(args.rail_code.join(" "), dialect) {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            log::fatal(&CONV, e);
+            std::process::exit(1);
+        }
+    };
 
-    let end_state = log::error_coerce(state.run_tokens(tokens));
+    let end_state = log::error_coerce(state.run_nodes(nodes));
 
     if !end_state.stack.is_empty() {
         log::error(&CONV, format!("State dump: {}", end_state.stack));
@@ -54,6 +82,24 @@ struct RailEvaluator {
     /// A file containing a line-separated list of library paths to preload.
     lib_list: Option<String>,
 
+    #[clap(long)]
+    /// Seed the random number generator, for reproducible runs.
+    seed: Option<i64>,
+
+    #[clap(long)]
+    /// Fail instead of warning when a program redefines a builtin or stdlib command.
+    deny_redefine: bool,
+
+    #[clap(long, value_enum, default_value = "rail")]
+    /// The language the code is written in.
+    dialect: Dialect,
+
     /// Code to evaluate
     rail_code: Vec<String>,
 }
+
+#[derive(Clone, ValueEnum)]
+enum Dialect {
+    Rail,
+    Forth,
+}
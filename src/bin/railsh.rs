@@ -1,4 +1,6 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use rail_lang::v1::docs::{self, DocFormat};
+use rail_lang::v1::loading::RAIL_SOURCE_CONVENTIONS;
 use rail_lang::v1::prompt::RailPrompt;
 use rail_lang::v1::{
     loading, log, RunConventions, RAIL_ERROR_PREFIX, RAIL_FATAL_PREFIX, RAIL_INFO_PREFIX,
@@ -15,25 +17,71 @@ const CONV: RunConventions = RunConventions {
     warn_prefix: RAIL_WARN_PREFIX,
     error_prefix: RAIL_ERROR_PREFIX,
     fatal_prefix: RAIL_FATAL_PREFIX,
+    deny_redefine: false,
+};
+
+const STRICT_CONV: RunConventions = RunConventions {
+    deny_redefine: true,
+    ..CONV
 };
 
 pub fn main() {
     let args = RailShell::parse();
 
-    let state = match loading::initial_rail_state(args.no_stdlib, args.lib_list, &CONV) {
+    let conventions = if args.deny_redefine {
+        &STRICT_CONV
+    } else {
+        &CONV
+    };
+
+    let state = match loading::initial_rail_state(args.no_stdlib, args.lib_list, conventions) {
         Ok(state) => state,
-        Err((state, err)) => {
+        Err(failure) => {
+            let (state, err) = *failure;
             log::error(&CONV, format!("Error loading initial state: {:?}", err));
             log::error(&CONV, format!("State dump: {}", state.stack));
             std::process::exit(1);
         }
     };
 
+    if let Some(seed) = args.seed {
+        state.rng.reseed(seed as u64);
+    }
+
+    let dialect = args.dialect.map(|dialect| match dialect {
+        Dialect::Rail => loading::Dialect::Rail,
+        Dialect::Forth => loading::Dialect::Forth,
+    });
+
     let end_state = match args.mode {
-        Some(Mode::Interactive) | None => RailPrompt::new(&CONV).run(state),
+        Some(Mode::Interactive) | None => RailPrompt::new(conventions)
+            .with_dialect(dialect.unwrap_or(loading::Dialect::Rail))
+            .run(state),
         Some(Mode::Run { file }) => {
-            let tokens = loading::get_source_file_as_tokens(file);
-            state.run_tokens(tokens)
+            let dialect = dialect.unwrap_or_else(|| RAIL_SOURCE_CONVENTIONS.dialect_of(&file));
+            let nodes = match loading::parse_source_file_in(&file, dialect) {
+                Ok(nodes) => nodes,
+                Err(e) => {
+                    log::fatal(&CONV, format!("{}: {:?}", file, e));
+                    std::process::exit(1);
+                }
+            };
+            state.run_nodes(nodes)
+        }
+        Some(Mode::Docs { format, output }) => {
+            let format = match format {
+                Format::Markdown => DocFormat::Markdown,
+                Format::Html => DocFormat::Html,
+            };
+            let reference = docs::reference(&state.definitions, format);
+            match output {
+                Some(path) => std::fs::write(&path, reference).unwrap_or_else(|e| {
+                    log::error(&CONV, format!("Unable to write {}: {}", path, e));
+                    std::process::exit(1);
+                }),
+                None => print!("{}", reference),
+            }
+            return;
         }
         Some(Mode::RunStdin) => {
             log::error(&CONV, "I don't know how to run stdin yet");
@@ -62,6 +110,18 @@ struct RailShell {
     #[clap(short = 'l', long)]
     /// A file containing a line-separated list of library paths to preload.
     lib_list: Option<String>,
+
+    #[clap(long)]
+    /// Seed the random number generator, for reproducible runs.
+    seed: Option<i64>,
+
+    #[clap(long)]
+    /// Fail instead of warning when a program redefines a builtin or stdlib command.
+    deny_redefine: bool,
+
+    #[clap(long, value_enum)]
+    /// The language to read. Files default to the one their extension names.
+    dialect: Option<Dialect>,
 }
 
 #[derive(Subcommand)]
@@ -74,7 +134,30 @@ enum Mode {
     /// Execute a file.
     Run { file: String },
 
+    /// Write a reference of every command, grouped by where it was defined.
+    Docs {
+        #[clap(long, value_enum, default_value = "markdown")]
+        /// The format of the reference.
+        format: Format,
+
+        #[clap(short, long)]
+        /// A file to write the reference to, instead of standard output.
+        output: Option<String>,
+    },
+
     #[clap(name = "-")]
     /// Read from standard input.
     RunStdin,
 }
+
+#[derive(Clone, ValueEnum)]
+enum Dialect {
+    Rail,
+    Forth,
+}
+
+#[derive(Clone, ValueEnum)]
+enum Format {
+    Markdown,
+    Html,
+}
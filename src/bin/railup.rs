@@ -14,6 +14,7 @@ const CONV: RunConventions = RunConventions {
     warn_prefix: RAIL_WARN_PREFIX,
     error_prefix: RAIL_ERROR_PREFIX,
     fatal_prefix: RAIL_FATAL_PREFIX,
+    deny_redefine: false,
 };
 
 pub fn main() {
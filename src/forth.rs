@@ -0,0 +1,347 @@
+//! A front end that reads simple Forth programs as Rail.
+//!
+//! Forth words are read case-insensitively. Control words become quotations
+//! and Rail commands that act the same way:
+//!
+//! * `: name ... ;` defines `name`, and `RECURSE` calls it.
+//! * `flag IF ... ELSE ... THEN` chooses on a boolean flag.
+//! * `limit start DO ... LOOP` runs with `I` bound to each index.
+//! * `VARIABLE x` defines `x` as 0, used with `x @`, `x !`, `x +!` and `x ?`.
+//! * `value CONSTANT x` defines `x`.
+//!
+//! Other Forth words with a different name in Rail, like `.` and `=`, are
+//! translated. Anything else is read as a Rail word.
+
+use crate::parser::{Node, ParseError};
+use crate::tokens::{self, LexError, LexErrorKind, Span, Token};
+
+/// Forth words with a different name or shape in Rail, and the Rail they become.
+const FORTH_WORDS: &[(&str, &str)] = &[
+    (".", "p \" \" p"),
+    (".s", "status"),
+    ("cr", "nl"),
+    ("space", "\" \" p"),
+    ("spaces", "[ \" \" p ] swap times"),
+    ("=", "eq?"),
+    ("<>", "eq? not"),
+    ("<", "gt?"),
+    (">", "lt?"),
+    ("0=", "0 eq?"),
+    ("0<", "0 gt?"),
+    ("0>", "0 lt?"),
+    ("invert", "not"),
+    ("1+", "1 +"),
+    ("1-", "1 -"),
+    ("2*", "2 *"),
+    ("2/", "2 /"),
+    ("over", "swap dup rot"),
+    ("rot", "rot rot"),
+    ("-rot", "rot"),
+    ("nip", "swap drop"),
+    ("tuck", "dup rot"),
+    ("2dup", "dup2"),
+    ("2drop", "drop drop"),
+];
+
+enum Word {
+    Word(String),
+    /// A string from `s" ..."`, or from `." ..."` to print it.
+    Str(String, bool),
+}
+
+enum Frame {
+    Colon(String),
+    If(Option<Vec<Node>>),
+    Do,
+}
+
+impl Frame {
+    fn opening_word(&self) -> &'static str {
+        match self {
+            Frame::Colon(_) => ":",
+            Frame::If(_) => "IF",
+            Frame::Do => "DO",
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Node>, ParseError> {
+    let words = words(source).map_err(ParseError::Lex)?;
+    let mut words = words.into_iter().peekable();
+
+    let mut frames: Vec<(Frame, Span, Vec<Node>)> = vec![];
+    let mut nodes = vec![];
+    let mut variables: Vec<String> = vec![];
+
+    let term = |name: &str, span| Node::Token(Token::Term(name.into()), span);
+    let string = |s: &str, span| Node::Token(Token::String(s.into()), span);
+
+    while let Some((word, span)) = words.next() {
+        let word = match word {
+            Word::Str(s, print) => {
+                nodes.push(string(&s, span));
+                if print {
+                    nodes.push(term("p", span));
+                }
+                continue;
+            }
+            Word::Word(word) => word.to_lowercase(),
+        };
+
+        let mut name_after = |word: &'static str| match words.next() {
+            Some((Word::Word(name), _)) => Ok(name.to_lowercase()),
+            _ => Err(ParseError::MissingName(word, span)),
+        };
+
+        match word.as_str() {
+            ":" => {
+                let name = name_after(":")?;
+                frames.push((Frame::Colon(name), span, std::mem::take(&mut nodes)));
+            }
+            "if" => frames.push((Frame::If(None), span, std::mem::take(&mut nodes))),
+            "do" => frames.push((Frame::Do, span, std::mem::take(&mut nodes))),
+            "else" => match frames.last_mut() {
+                Some((Frame::If(when_true @ None), _, _)) => {
+                    *when_true = Some(std::mem::take(&mut nodes));
+                }
+                _ => return Err(ParseError::Unopened("ELSE", "IF", span)),
+            },
+            ";" | "then" | "loop" => {
+                let (frame, start, parent) = match (word.as_str(), frames.pop()) {
+                    (";", Some(frame @ (Frame::Colon(_), _, _)))
+                    | ("then", Some(frame @ (Frame::If(_), _, _)))
+                    | ("loop", Some(frame @ (Frame::Do, _, _))) => frame,
+                    (_, Some((frame, start, _))) => {
+                        return Err(ParseError::Unclosed(frame.opening_word(), start))
+                    }
+                    (";", _) => return Err(ParseError::Unopened(";", ":", span)),
+                    ("then", _) => return Err(ParseError::Unopened("THEN", "IF", span)),
+                    _ => return Err(ParseError::Unopened("LOOP", "DO", span)),
+                };
+                let body = std::mem::replace(&mut nodes, parent);
+                nodes.extend(close(frame, body, start));
+            }
+            "recurse" => {
+                let name = frames.iter().rev().find_map(|(frame, _, _)| match frame {
+                    Frame::Colon(name) => Some(name.clone()),
+                    _ => None,
+                });
+                match name {
+                    Some(name) => nodes.push(term(&name, span)),
+                    None => return Err(ParseError::Unopened("RECURSE", ":", span)),
+                }
+            }
+            "variable" => {
+                let name = name_after("VARIABLE")?;
+                nodes.push(Node::Token(Token::I64(0), span));
+                nodes.push(Node::Quote(vec![term(&name, span)], span));
+                nodes.push(term("=>", span));
+                variables.push(name);
+            }
+            "constant" => {
+                let name = name_after("CONSTANT")?;
+                nodes.push(term("quote", span));
+                nodes.push(string(&name, span));
+                nodes.push(term("def!", span));
+            }
+            name if variables.iter().any(|v| v == name) => {
+                let access = match words.peek() {
+                    Some((Word::Word(access), _)) => access.clone(),
+                    _ => String::new(),
+                };
+                // A variable is a binding, so storing replaces its value in place.
+                let store = |nodes: &mut Vec<Node>| {
+                    nodes.push(Node::Quote(vec![term(name, span)], span));
+                    nodes.push(term("=>", span));
+                };
+                match access.as_str() {
+                    "@" => nodes.push(term(name, span)),
+                    "!" => store(&mut nodes),
+                    "+!" => {
+                        nodes.push(term(name, span));
+                        nodes.push(term("+", span));
+                        store(&mut nodes);
+                    }
+                    "?" => {
+                        nodes.push(term(name, span));
+                        nodes.extend(rail(".", span));
+                    }
+                    _ => {
+                        nodes.push(term(name, span));
+                        continue;
+                    }
+                }
+                words.next();
+            }
+            word => nodes.extend(rail(word, span)),
+        }
+    }
+
+    match frames.pop() {
+        Some((frame, start, _)) => Err(ParseError::Unclosed(frame.opening_word(), start)),
+        None => Ok(nodes),
+    }
+}
+
+/// The Rail for a finished control structure.
+fn close(frame: Frame, body: Vec<Node>, span: Span) -> Vec<Node> {
+    let term = |name: &str| Node::Token(Token::Term(name.into()), span);
+    let quote = |nodes: Vec<Node>| Node::Quote(nodes, span);
+
+    match frame {
+        Frame::Colon(name) => vec![
+            quote(body),
+            Node::Token(Token::String(name), span),
+            term("def!"),
+        ],
+        Frame::If(when_true) => {
+            let (when_true, when_false) = match when_true {
+                Some(when_true) => (when_true, body),
+                None => (body, vec![]),
+            };
+            let branch =
+                |nodes: Vec<Node>| quote([term("drop")].into_iter().chain(nodes).collect());
+            vec![
+                quote(vec![
+                    quote(vec![]),
+                    branch(when_true),
+                    quote(vec![Node::Token(Token::Boolean(true), span)]),
+                    branch(when_false),
+                ]),
+                term("?"),
+            ]
+        }
+        Frame::Do => {
+            // Each pass shadows I with its index and forgets it after, so an
+            // outer loop's I is back once an inner loop ends.
+            let index = || Node::Token(Token::String("i".into()), span);
+            let bind_index = [term("quote"), index(), term("def!")];
+            let forget_index = [index(), term("forget")];
+            vec![
+                term("swap"),
+                Node::Token(Token::I64(1), span),
+                term("-"),
+                Node::Token(Token::I64(1), span),
+                term("range"),
+                quote(
+                    bind_index
+                        .into_iter()
+                        .chain(body)
+                        .chain(forget_index)
+                        .collect(),
+                ),
+                term("each!"),
+            ]
+        }
+    }
+}
+
+/// A Forth word as Rail: numbers as they are, translated words as their
+/// translation, and any other word as a Rail word of the same name.
+fn rail(word: &str, span: Span) -> Vec<Node> {
+    if let Some((_, translation)) = FORTH_WORDS.iter().find(|(forth, _)| *forth == word) {
+        return tokens::tokenize(translation)
+            .unwrap()
+            .into_iter()
+            .map(|token| Node::Token(token, span))
+            .collect();
+    }
+
+    match Token::from(word.to_string()) {
+        token @ (Token::I64(_) | Token::F64(_) | Token::Boolean(_)) => {
+            vec![Node::Token(token, span)]
+        }
+        _ => vec![Node::Token(Token::Term(word.to_string()), span)],
+    }
+}
+
+/// Split Forth source into words and strings, skipping comments.
+fn words(source: &str) -> Result<Vec<(Word, Span)>, LexError> {
+    let mut scanner = Scanner::new(source);
+    let mut words = vec![];
+
+    loop {
+        scanner.take_while(char::is_whitespace);
+        let span = scanner.span();
+        let word = scanner.take_while(|c| !c.is_whitespace());
+
+        match word.to_lowercase().as_str() {
+            "" => return Ok(words),
+            "\\" => {
+                scanner.upto('\n');
+            }
+            "(" => {
+                scanner
+                    .upto(')')
+                    .ok_or_else(|| unterminated("comment", span))?;
+            }
+            ".\"" | "s\"" => {
+                // The space between the word and the string is not part of it.
+                scanner.next();
+                let text = scanner
+                    .upto('"')
+                    .ok_or_else(|| unterminated("string", span))?;
+                words.push((Word::Str(text, word == ".\""), span));
+            }
+            _ => words.push((Word::Word(word), span)),
+        }
+    }
+}
+
+fn unterminated(what: &str, span: Span) -> LexError {
+    LexError {
+        kind: LexErrorKind::Unterminated,
+        message: format!("Unterminated {}", what),
+        line: span.line,
+        column: span.column,
+    }
+}
+
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(source: &'a str) -> Self {
+        Scanner {
+            chars: source.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn span(&self) -> Span {
+        Span {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.chars.peek().copied().filter(|c| keep(*c)) {
+            self.next();
+            taken.push(c);
+        }
+        taken
+    }
+
+    /// Everything up to the end character, which is skipped, if there is one.
+    fn upto(&mut self, end: char) -> Option<String> {
+        let taken = self.take_while(|c| c != end);
+        self.next().map(|_| taken)
+    }
+}
@@ -1,3 +1,5 @@
+pub mod forth;
+pub mod parser;
 pub mod tokens;
 pub mod v1;
 
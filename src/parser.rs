@@ -0,0 +1,120 @@
+use crate::tokens::{self, LexError, LexErrorKind, Span, Token};
+
+/// Source as a tree, where each pair of brackets is a quotation of the nodes between them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Token(Token, Span),
+    /// A quotation, starting at its `[`.
+    Quote(Vec<Node>, Span),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    Lex(LexError),
+    /// An opening word, like `[`, that is never closed.
+    Unclosed(&'static str, Span),
+    /// A closing word, like `]`, and the opening word it has no match for.
+    Unopened(&'static str, &'static str, Span),
+    /// A defining word, like Forth's `:`, with no name after it.
+    MissingName(&'static str, Span),
+}
+
+impl ParseError {
+    /// Whether more source could finish what was started, like an open
+    /// quotation or string.
+    pub fn is_incomplete(&self) -> bool {
+        match self {
+            ParseError::Lex(e) => e.kind == LexErrorKind::Unterminated,
+            ParseError::Unclosed(..) | ParseError::MissingName(..) => true,
+            ParseError::Unopened(..) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Lex(e) => write!(f, "{}", e),
+            ParseError::Unclosed("[", span) => {
+                write!(f, "Unbalanced brackets. The [ at {} is never closed", span)
+            }
+            ParseError::Unclosed(open, span) => {
+                write!(f, "The {} at {} is never closed", open, span)
+            }
+            ParseError::Unopened("]", "[", span) => {
+                write!(
+                    f,
+                    "Unbalanced brackets. The ] at {} has no matching [",
+                    span
+                )
+            }
+            ParseError::Unopened(close, open, span) => {
+                write!(f, "The {} at {} has no matching {}", close, span, open)
+            }
+            ParseError::MissingName(word, span) => {
+                write!(f, "The {} at {} needs a name after it", word, span)
+            }
+        }
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Node>, ParseError> {
+    let tokens = tokens::tokenize_spanned(source).map_err(ParseError::Lex)?;
+    parse_tokens(tokens)
+}
+
+/// Build the tree of quotations, checking that every bracket is balanced.
+pub fn parse_tokens(tokens: Vec<(Token, Span)>) -> Result<Vec<Node>, ParseError> {
+    let mut open: Vec<(Vec<Node>, Span)> = vec![];
+    let mut nodes = vec![];
+
+    for (token, span) in tokens {
+        match token {
+            Token::LeftBracket => open.push((std::mem::take(&mut nodes), span)),
+            Token::RightBracket => match open.pop() {
+                Some((mut parent, start)) => {
+                    parent.push(Node::Quote(nodes, start));
+                    nodes = parent;
+                }
+                None => return Err(ParseError::Unopened("]", "[", span)),
+            },
+            token => nodes.push(Node::Token(token, span)),
+        }
+    }
+
+    match open.pop() {
+        Some((_, start)) => Err(ParseError::Unclosed("[", start)),
+        None => Ok(nodes),
+    }
+}
+
+#[test]
+fn parse_test() {
+    let span = |line, column| Span { line, column };
+    let expected = vec![
+        Node::Token(Token::I64(1), span(1, 1)),
+        Node::Quote(
+            vec![
+                Node::Quote(vec![], span(1, 5)),
+                Node::Token(Token::Term("dup".into()), span(2, 1)),
+            ],
+            span(1, 3),
+        ),
+    ];
+
+    assert_eq!(Ok(expected), parse("1 [ [ ]\ndup ]"));
+}
+
+#[test]
+fn parse_test_2() {
+    let span = |line, column| Span { line, column };
+
+    assert_eq!(
+        Err(ParseError::Unclosed("[", span(1, 3))),
+        parse("1 [ [ 2 ]")
+    );
+    assert_eq!(
+        Err(ParseError::Unopened("]", "[", span(2, 3))),
+        parse("[ 1 ]\n2 ] [")
+    );
+}
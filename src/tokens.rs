@@ -1,5 +1,3 @@
-use regex::Regex;
-
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     LeftBracket,
@@ -10,6 +8,10 @@ pub enum Token {
     Term(std::string::String),
     DeferredTerm(std::string::String),
     String(std::string::String),
+    /// A `#:` comment, documenting the definition that follows it.
+    DocComment(std::string::String),
+    /// A `( a b -- c )` comment, as the names before and after the `--`.
+    StackEffect(Vec<std::string::String>, Vec<std::string::String>),
     None,
 }
 
@@ -25,18 +27,8 @@ impl From<std::string::String> for Token {
             Boolean(true)
         } else if &tok == "false" {
             Boolean(false)
-        } else if &tok != "\"" && tok.starts_with('"') && tok.ends_with('"') {
-            let s = tok
-                .strip_prefix('"')
-                .unwrap()
-                .strip_suffix('"')
-                .unwrap()
-                .replace("\\n", "\n");
-            String(s)
-        } else if let Ok(i) = tok.parse::<i64>() {
-            I64(i)
-        } else if let Ok(n) = tok.parse::<f64>() {
-            F64(n)
+        } else if let Some(number) = number(&tok) {
+            number
         } else if tok.starts_with('\\') {
             let term = tok.strip_prefix('\\').unwrap().trim().to_string();
             if term.is_empty() {
@@ -50,19 +42,429 @@ impl From<std::string::String> for Token {
     }
 }
 
-pub fn tokenize(line: &str) -> Vec<Token> {
-    // TODO: Validate that a line does not contain unterminated strings.
-    // TODO: Handle character escapes for quotes, newlines, etc. (But here?)
-    let re: Regex = Regex::new(r#"(".*?"|\[|\]|[^\s\[\]]*)"#).unwrap();
-    let line = line.replace('\n', " ");
-    re.captures_iter(&line)
-        .flat_map(|cap| cap.iter().take(1).collect::<Vec<_>>())
-        .filter_map(|res| res.map(|mat| mat.as_str()))
-        .take_while(|s| !s.starts_with('#'))
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_owned())
-        .map(Token::from)
-        .collect()
+/// A word is a number when it starts with a digit, after an optional sign and
+/// decimal point, and the rest of it makes sense as one. Otherwise it's a word,
+/// so `inf`, `NaN` and `4_million` are all words.
+///
+/// Numbers may have `_` between digits, a `0x`, `0o` or `0b` prefix for
+/// integers in another base, and an `i64` or `f64` suffix to pick the type.
+/// Unsuffixed numbers are integers if they fit, and floats otherwise.
+fn number(word: &str) -> Option<Token> {
+    let (negative, unsigned) = match word.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        Option::None => (false, word.strip_prefix('+').unwrap_or(word)),
+    };
+    if !unsigned
+        .strip_prefix('.')
+        .unwrap_or(unsigned)
+        .starts_with(|c: char| c.is_ascii_digit())
+    {
+        return Option::None;
+    }
+
+    let radix = match unsigned.get(..2) {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        Some("0b") => Some(2),
+        _ => Option::None,
+    };
+
+    if let Some(radix) = radix {
+        let digits = unsigned[2..].strip_suffix("i64").unwrap_or(&unsigned[2..]);
+        let digits = without_separators(digits)?;
+        let sign = if negative { "-" } else { "" };
+        return i64::from_str_radix(&format!("{}{}", sign, digits), radix)
+            .ok()
+            .map(I64);
+    }
+
+    let (digits, suffix) = match (unsigned.strip_suffix("i64"), unsigned.strip_suffix("f64")) {
+        (Some(digits), _) => (digits, Some("i64")),
+        (_, Some(digits)) => (digits, Some("f64")),
+        _ => (unsigned, Option::None),
+    };
+    let digits = without_separators(digits)?;
+    let digits = if negative {
+        format!("-{}", digits)
+    } else {
+        digits
+    };
+
+    match suffix {
+        Some("i64") => digits
+            .parse::<i64>()
+            .ok()
+            .or_else(|| {
+                digits
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| n.fract() == 0.0 && n.abs() < i64::MAX as f64)
+                    .map(|n| n as i64)
+            })
+            .map(I64),
+        Some(_) => digits.parse::<f64>().ok().map(F64),
+        Option::None => match digits.parse::<i64>() {
+            Ok(i) => Some(I64(i)),
+            Err(_) => digits.parse::<f64>().ok().map(F64),
+        },
+    }
+}
+
+/// Digits without their `_` separators, as long as each separator sits
+/// between two digits.
+fn without_separators(digits: &str) -> Option<std::string::String> {
+    let chars = digits.chars().collect::<Vec<_>>();
+    let well_placed = chars.iter().enumerate().all(|(i, c)| {
+        *c != '_'
+            || (i > 0
+                && i + 1 < chars.len()
+                && chars[i - 1].is_ascii_alphanumeric()
+                && chars[i + 1].is_ascii_alphanumeric())
+    });
+    well_placed.then(|| digits.replace('_', ""))
+}
+
+/// Where something starts in the source, counting lines and columns from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// A problem turning source into tokens, and where in the source it happened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub message: std::string::String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LexErrorKind {
+    /// The source ends before something it started, like a string, is finished.
+    Unterminated,
+    /// The source can't be read, however it goes on, like an unknown escape.
+    Invalid,
+}
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+/// Escape a string so it can be written between double quotes in Rail source.
+pub fn escape(s: &str) -> std::string::String {
+    let mut escaped = std::string::String::new();
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Split source into tokens. Strings may span lines, and a character literal
+/// like `'a'` is its code point.
+///
+/// A `#` on its own starts a comment to the end of the line, as does `#!` at
+/// the very start of the source and `#:` for doc comments. Block comments go
+/// between `#[` and `]#`, and may nest. Words like `#tag` are just words.
+/// Between `(` and `)` is a comment too, kept as a stack effect if it has a `--`.
+pub fn tokenize(source: &str) -> Result<Vec<Token>, LexError> {
+    let tokens = tokenize_spanned(source)?;
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Split source into tokens, along with where each one starts.
+pub fn tokenize_spanned(source: &str) -> Result<Vec<(Token, Span)>, LexError> {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = vec![];
+
+    while let Some(c) = lexer.peek() {
+        let span = lexer.span();
+        match c {
+            c if c.is_whitespace() => {
+                lexer.next();
+            }
+            '[' => {
+                lexer.next();
+                tokens.push((LeftBracket, span));
+            }
+            ']' => {
+                lexer.next();
+                tokens.push((RightBracket, span));
+            }
+            '"' => tokens.push((String(lexer.string()?), span)),
+            '\'' => match lexer.character()? {
+                Some(c) => tokens.push((I64(c as i64), span)),
+                Option::None => tokens.push((Token::from(lexer.word()), span)),
+            },
+            'r' if lexer.at_raw_string() => tokens.push((String(lexer.raw_string()?), span)),
+            '#' if lexer.rest().starts_with("#[") => lexer.block_comment()?,
+            '#' if lexer.at_line_comment() => {
+                let comment = lexer.rest_of_line();
+                if let Some(doc) = comment.strip_prefix("#:") {
+                    tokens.push((DocComment(doc.trim().to_string()), span));
+                }
+            }
+            '(' if lexer.at_word("(") => {
+                if let Some(effect) = lexer.stack_effect()? {
+                    tokens.push((effect, span));
+                }
+            }
+            _ => tokens.push((Token::from(lexer.word()), span)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Lexer {
+            source,
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn span(&self) -> Span {
+        Span {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str, (line, column): (usize, usize)) -> LexError {
+        LexError {
+            kind: LexErrorKind::Invalid,
+            message: message.to_string(),
+            line,
+            column,
+        }
+    }
+
+    fn unterminated(&self, message: &str, start: (usize, usize)) -> LexError {
+        LexError {
+            kind: LexErrorKind::Unterminated,
+            ..self.error(message, start)
+        }
+    }
+
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&keep) {
+            self.next();
+        }
+        &self.source[start..self.pos]
+    }
+
+    fn word(&mut self) -> std::string::String {
+        self.take_while(|c| !c.is_whitespace() && c != '[' && c != ']')
+            .to_string()
+    }
+
+    fn rest_of_line(&mut self) -> &'a str {
+        self.take_while(|c| c != '\n')
+    }
+
+    /// Whether the next word is exactly `word`.
+    fn at_word(&self, word: &str) -> bool {
+        self.rest()
+            .strip_prefix(word)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    }
+
+    fn at_line_comment(&self) -> bool {
+        self.at_word("#")
+            || self.rest().starts_with("#:")
+            || (self.pos == 0 && self.rest().starts_with("#!"))
+    }
+
+    fn block_comment(&mut self) -> Result<(), LexError> {
+        let start = (self.line, self.column);
+        let mut depth = 0;
+
+        loop {
+            if self.rest().starts_with("#[") {
+                depth += 1;
+                self.next();
+            } else if self.rest().starts_with("]#") {
+                depth -= 1;
+                self.next();
+            }
+
+            if self.next().is_none() {
+                return Err(self.unterminated("Unterminated block comment", start));
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn stack_effect(&mut self) -> Result<Option<Token>, LexError> {
+        let start = (self.line, self.column);
+        self.next();
+
+        let mut words = vec![];
+        loop {
+            self.take_while(char::is_whitespace);
+            match self.take_while(|c| !c.is_whitespace()) {
+                "" => return Err(self.unterminated("Unterminated stack effect comment", start)),
+                ")" => break,
+                word => words.push(word.to_string()),
+            }
+        }
+
+        Ok(words
+            .iter()
+            .position(|w| w == "--")
+            .map(|split| StackEffect(words[..split].to_vec(), words[split + 1..].to_vec())))
+    }
+
+    /// Whether the lexer is at a raw string, like `r"C:\path"` or `r#"say "hi""#`.
+    fn at_raw_string(&self) -> bool {
+        let hashes = self.rest()[1..].chars().take_while(|c| *c == '#').count();
+        self.rest()[1 + hashes..].starts_with('"')
+    }
+
+    fn raw_string(&mut self) -> Result<std::string::String, LexError> {
+        let start = (self.line, self.column);
+        self.next();
+        let hashes = self.take_while(|c| c == '#').len();
+        self.next();
+
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        match self.rest().find(&terminator) {
+            Some(len) => {
+                let s = self.rest()[..len].to_string();
+                for _ in s.chars().chain(terminator.chars()) {
+                    self.next();
+                }
+                Ok(s)
+            }
+            Option::None => Err(self.unterminated("Unterminated raw string", start)),
+        }
+    }
+
+    fn string(&mut self) -> Result<std::string::String, LexError> {
+        let start = (self.line, self.column);
+        self.next();
+
+        let mut s = std::string::String::new();
+        loop {
+            let escape_start = (self.line, self.column);
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape(escape_start)?),
+                Some(c) => s.push(c),
+                Option::None => return Err(self.unterminated("Unterminated string", start)),
+            }
+        }
+    }
+
+    /// A character literal like `'a'` or `'\n'`, if the lexer is at one.
+    fn character(&mut self) -> Result<Option<char>, LexError> {
+        let start = (self.line, self.column);
+        let mut chars = self.rest().chars().skip(1);
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some('\\'), _, _) => {
+                self.next();
+                let escape_start = (self.line, self.column);
+                self.next();
+                let c = self.escape(escape_start)?;
+                match self.next() {
+                    Some('\'') => Ok(Some(c)),
+                    Some(_) => Err(self.error("Unterminated character literal", start)),
+                    Option::None => Err(self.unterminated("Unterminated character literal", start)),
+                }
+            }
+            (Some(c), Some('\''), after)
+                if c != '\'' && after.is_none_or(|a| a.is_whitespace() || a == '[' || a == ']') =>
+            {
+                self.next();
+                self.next();
+                self.next();
+                Ok(Some(c))
+            }
+            _ => Ok(Option::None),
+        }
+    }
+
+    fn escape(&mut self, start: (usize, usize)) -> Result<char, LexError> {
+        match self.next() {
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('0') => Ok('\0'),
+            Some('\'') => Ok('\''),
+            Some('u') => {
+                if self.next() != Some('{') {
+                    return Err(self.error("Invalid unicode escape", start));
+                }
+                let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                if self.next() != Some('}') {
+                    return Err(self.error("Invalid unicode escape", start));
+                }
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("Invalid unicode escape", start))
+            }
+            Some(c) => Err(self.error(&format!("Unknown escape \\{}", c), start)),
+            Option::None => Err(self.unterminated("Unterminated string", start)),
+        }
+    }
 }
 
 #[test]
@@ -70,7 +472,7 @@ fn token_test() {
     let actual = "1 1 +";
     let expected = vec![I64(1), I64(1), Term("+".into())];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -78,7 +480,7 @@ fn token_test_2() {
     let actual = "\"hello\" \"there\"";
     let expected = vec![String("hello".into()), String("there".into())];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -86,7 +488,7 @@ fn token_test_3() {
     let actual = "\"hello there\"";
     let expected = vec![String("hello there".into())];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -94,7 +496,7 @@ fn token_test_4() {
     let actual = "\" hello there \"";
     let expected = vec![String(" hello there ".into())];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -108,15 +510,15 @@ fn token_test_5() {
         I64(5),
     ];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
 fn token_test_6() {
-    let actual = "1 2 \"a # in a string is fine\" #but at the end is ignored";
+    let actual = "1 2 \"a # in a string is fine\" # but at the end is ignored";
     let expected = vec![I64(1), I64(2), String("a # in a string is fine".into())];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -132,7 +534,7 @@ fn token_test_7() {
         Term(".s".into()),
     ];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -148,7 +550,7 @@ fn token_test_8() {
         Term(".s".into()),
     ];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -166,7 +568,7 @@ fn token_test_9() {
         Term(".s".into()),
     ];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -179,7 +581,7 @@ fn token_test_10() {
         Term(".s".into()),
     ];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
 }
 
 #[test]
@@ -193,5 +595,179 @@ fn token_test_11() {
         Term("pl".into()),
     ];
 
-    assert_eq!(expected, tokenize(actual));
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_12() {
+    let actual = "1 2 + #: Adds one and two.";
+    let expected = vec![
+        I64(1),
+        I64(2),
+        Term("+".into()),
+        DocComment("Adds one and two.".into()),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_13() {
+    let actual = r#"1 "say \"hi\"\\" 2"#;
+    let expected = vec![I64(1), String("say \"hi\"\\".into()), I64(2)];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_14() {
+    let actual = r#""tab\there\r\n" "nul\0" "\u{1F682} \u{e9}""#;
+    let expected = vec![
+        String("tab\there\r\n".into()),
+        String("nul\0".into()),
+        String("\u{1F682} \u{e9}".into()),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_15() {
+    let actual = "r\"C:\\rail\\src\" r#\"say \"hi\"\"# rail";
+    let expected = vec![
+        String("C:\\rail\\src".into()),
+        String("say \"hi\"".into()),
+        Term("rail".into()),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_16() {
+    let actual = "\"first\nsecond\" # not a \"string\n3";
+    let expected = vec![String("first\nsecond".into()), I64(3)];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_17() {
+    let actual = "1 2\n  \"abc";
+    let expected = LexError {
+        kind: LexErrorKind::Unterminated,
+        message: "Unterminated string".into(),
+        line: 2,
+        column: 3,
+    };
+
+    assert_eq!(Err(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_18() {
+    let actual = "\"a\\qb\"";
+    let expected = LexError {
+        kind: LexErrorKind::Invalid,
+        message: "Unknown escape \\q".into(),
+        line: 1,
+        column: 3,
+    };
+
+    assert_eq!(Err(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_19() {
+    let actual = "0xff -0x10 0o17 0b1010 1_000_000 1e9i64 3f64 2.5 .5 1e3";
+    let expected = vec![
+        I64(255),
+        I64(-16),
+        I64(15),
+        I64(10),
+        I64(1_000_000),
+        I64(1_000_000_000),
+        F64(3.0),
+        F64(2.5),
+        F64(0.5),
+        F64(1000.0),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_20() {
+    let actual = "inf NaN 4_million 1__0 0x 1e3x - .s";
+    let expected = vec![
+        Term("inf".into()),
+        Term("NaN".into()),
+        Term("4_million".into()),
+        Term("1__0".into()),
+        Term("0x".into()),
+        Term("1e3x".into()),
+        Term("-".into()),
+        Term(".s".into()),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_21() {
+    let actual = r"'a' '\n' '\'' '[' 'quoted' 'x";
+    let expected = vec![
+        I64(97),
+        I64(10),
+        I64(39),
+        I64(91),
+        Term("'quoted'".into()),
+        Term("'x".into()),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_22() {
+    let actual = "#!/usr/bin/env rail\n1 #[ 2 #[ 3 ]# 4 ]# #tag # 5\n6";
+    let expected = vec![I64(1), Term("#tag".into()), I64(6)];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_23() {
+    let actual = "[ ( a b -- c ) + ] ( just a comment ) (x)";
+    let expected = vec![
+        LeftBracket,
+        StackEffect(vec!["a".into(), "b".into()], vec!["c".into()]),
+        Term("+".into()),
+        RightBracket,
+        Term("(x)".into()),
+    ];
+
+    assert_eq!(Ok(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_24() {
+    let actual = "1\n#[ 2 #[ 3 ]#";
+    let expected = LexError {
+        kind: LexErrorKind::Unterminated,
+        message: "Unterminated block comment".into(),
+        line: 2,
+        column: 1,
+    };
+
+    assert_eq!(Err(expected), tokenize(actual));
+}
+
+#[test]
+fn token_test_25() {
+    let unterminated = tokenize("'\\n").map_err(|e| e.kind);
+    assert_eq!(Err(LexErrorKind::Unterminated), unterminated);
+
+    let invalid = tokenize("'\\nx").map_err(|e| e.kind);
+    assert_eq!(Err(LexErrorKind::Invalid), invalid);
 }
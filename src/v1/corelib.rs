@@ -1,16 +1,23 @@
-use crate::v1::rail_machine::{self, Dictionary};
+use crate::v1::rail_machine::{self, Dictionary, RailDef};
 
 mod bool;
 mod choice;
 mod command;
 mod display;
+mod dtab;
 mod filesystem;
+mod generator;
 mod math;
 mod meta;
+mod option;
 mod process;
+mod random;
+mod record;
 mod repeat;
 mod sequence;
+mod set;
 mod shuffle;
+mod source;
 mod stab;
 mod string;
 mod test;
@@ -18,21 +25,35 @@ mod test;
 pub fn rail_builtin_dictionary() -> Dictionary {
     rail_machine::dictionary_of(
         [
-            bool::builtins(),
-            choice::builtins(),
-            command::builtins(),
-            display::builtins(),
-            filesystem::builtins(),
-            math::builtins(),
-            meta::builtins(),
-            process::builtins(),
-            repeat::builtins(),
-            shuffle::builtins(),
-            sequence::builtins(),
-            stab::builtins(),
-            string::builtins(),
-            test::builtins(),
+            from_module("bool", bool::builtins()),
+            from_module("choice", choice::builtins()),
+            from_module("command", command::builtins()),
+            from_module("display", display::builtins()),
+            from_module("dtab", dtab::builtins()),
+            from_module("filesystem", filesystem::builtins()),
+            from_module("generator", generator::builtins()),
+            from_module("math", math::builtins()),
+            from_module("meta", meta::builtins()),
+            from_module("option", option::builtins()),
+            from_module("process", process::builtins()),
+            from_module("random", random::builtins()),
+            from_module("record", record::builtins()),
+            from_module("repeat", repeat::builtins()),
+            from_module("shuffle", shuffle::builtins()),
+            from_module("source", source::builtins()),
+            from_module("sequence", sequence::builtins()),
+            from_module("set", set::builtins()),
+            from_module("stab", stab::builtins()),
+            from_module("string", string::builtins()),
+            from_module("test", test::builtins()),
         ]
         .concat(),
     )
 }
+
+fn from_module(module: &str, defs: Vec<RailDef<'static>>) -> Vec<RailDef<'static>> {
+    let origin = format!("corelib/{}", module);
+    defs.into_iter()
+        .map(|def| def.with_origin(&origin))
+        .collect()
+}
@@ -25,23 +25,6 @@ pub fn builtins() -> Vec<RailDef<'static>> {
         binary_numeric_pred("lt?", "Consumes two numbers. If the top value is lesser, produces true. Otherwise produces false.", |a, b| b < a, |a, b| b < a),
         binary_numeric_pred("gte?", "Consumes two numbers. If the top value is greater or equal, produces true. Otherwise produces false.", |a, b| b >= a, |a, b| b >= a),
         binary_numeric_pred("lte?", "Consumes two numbers. If the top value is lesser or equal, produces true. Otherwise produces false.", |a, b| b <= a, |a, b| b <= a),
-        RailDef::on_state_noerr("any", "Consumes a sequence and a predicate. If the predicate applied to any value in the sequence is true, produces true. Otherwise produces false.", &[Quote, Quote], &[Quote], |state| {
-            let (predicate, state) = state.pop_quote("any");
-            let (sequence, state) = state.pop_quote("any");
-
-            for term in sequence.stack.values {
-                let substate = state.child().push(term);
-                let substate = predicate.clone().run_in_state(substate);
-                if let Ok(substate) = substate {
-                    let (pass, _) = substate.stack.pop_bool("any");
-                    if pass {
-                        return state.push_bool(true);
-                    }
-                }
-            }
-
-            state.push_bool(false)
-        }),
     ]
 }
 
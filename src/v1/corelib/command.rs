@@ -1,6 +1,8 @@
 use crate::v1::{
-    log,
-    rail_machine::{RailDef, RailRunResult, RailState, RailType, RailVal},
+    log, pattern,
+    rail_machine::{
+        Dictionary, RailDef, RailError, RailRunResult, RailState, RailType, RailVal, Stack,
+    },
 };
 
 use RailType::*;
@@ -8,6 +10,9 @@ use RailType::*;
 const DEFINITIONS_PRESERVED: &str = "Any new definitions are preserved in the calling context.";
 const DEFINITIONS_LOCALY_ONLY: &str =
     "Any definitions are local only to the quote or command performed.";
+const DOCUMENTATION: &str = "Any #: comments just before it become the command's description.";
+const UNDOCUMENTED: &str = "Undocumented.";
+const DESTRUCTURING: &str = "Names may be commands or strings. A nested quote takes apart a quote by position, a symbol table by key + pattern pairs, or a record by variant and fields. Literals must match exactly, and _ matches anything without binding it.";
 
 pub fn builtins() -> Vec<RailDef<'static>> {
     vec![
@@ -27,9 +32,13 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             &[Unknown],
             doin(),
         ),
-        RailDef::on_state_noerr("def!", &format!("{} {}", "Consumes one quote and a quoted command or string. The latter quoted command or string becomes a command that executes the first quote.", DEFINITIONS_PRESERVED), &[Quote, QuoteOrCommand], &[], |state| {
+        RailDef::on_state("def!", &format!("{} {} {}", "Consumes one quote and a quoted command or string. The latter quoted command or string becomes a command that executes the first quote.", DOCUMENTATION, DEFINITIONS_PRESERVED), &[Quote, QuoteOrCommand], &[], |state| {
+            let state = check_redefinition(state, 0)?;
             let conventions = state.conventions;
-            state.update_stack_and_defs(|quote, definitions| {
+            let origin = state.origin_name();
+            let (doc, state) = state.take_doc();
+            let description = doc.unwrap_or_else(|| UNDOCUMENTED.to_string());
+            Ok(state.update_stack_and_defs(|quote, definitions| {
                 let mut definitions = definitions;
 
                 let (name, quote) = quote.pop();
@@ -43,21 +52,132 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                     return (quote, definitions);
                 };
 
-                // FIXME: Should be from the quote.
-                let description = "FIXME: Undocumented";
-
                 let (commands, quote) = quote.pop_quote("def!");
                 // TODO: Typecheck...?
                 definitions.insert(
                     name.clone(),
-                    RailDef::from_quote(&name, description, commands),
+                    RailDef::from_quote(&name, &description, commands).with_origin(&origin),
                 );
                 (quote, definitions)
-            })
+            }))
+        }),
+        RailDef::on_state("def-typed!", &format!("Consumes one quote, a quoted command or string, and a quote of type names as a signature like [ i64 string -- string ]. The command executes the first quote, checking the types it consumes and produces. {} {}", DOCUMENTATION, DEFINITIONS_PRESERVED), &[Quote, QuoteOrCommand, Quote], &[], |state| {
+            let state = check_redefinition(state, 1)?;
+            let conventions = state.conventions;
+            let origin = state.origin_name();
+            let (doc, state) = state.take_doc();
+            let description = doc.unwrap_or_else(|| UNDOCUMENTED.to_string());
+            Ok(state.update_stack_and_defs(|quote, mut definitions| {
+                let (signature, quote) = quote.pop_quote("def-typed!");
+                let (name, quote) = quote.pop();
+                let (commands, quote) = quote.pop_quote("def-typed!");
+
+                let name = get_command_name(&name);
+                let signature = signature.stack.values.iter().map(get_command_name).collect::<Option<Vec<_>>>();
+                let (name, signature) = if let (Some(name), Some(signature)) = (name, signature) {
+                    (name, signature)
+                } else {
+                    log::warn(conventions, "def-typed! wants a string or command as a name and a signature of type names".to_string());
+                    return (quote, definitions);
+                };
+
+                let (consumes, produces) = match signature.iter().position(|t| t == "--") {
+                    Some(i) => (&signature[..i], &signature[i + 1..]),
+                    None => {
+                        log::warn(conventions, format!("The signature of {} needs a -- between what it consumes and produces", name));
+                        return (quote, definitions);
+                    }
+                };
+                let (consumes, produces) = match (types_named(consumes, &definitions), types_named(produces, &definitions)) {
+                    (Ok(consumes), Ok(produces)) => (consumes, produces),
+                    (Err(t), _) | (_, Err(t)) => {
+                        log::warn(conventions, format!("The signature of {} names {}, which is not a type", name, t));
+                        return (quote, definitions);
+                    }
+                };
+
+                definitions.insert(
+                    name.clone(),
+                    RailDef::from_typed_quote(&name, &description, consumes, produces, commands).with_origin(&origin),
+                );
+                (quote, definitions)
+            }))
         }),
-        RailDef::on_state_noerr("alias", &format!("Consumes two commands, and binds the latter to the former. {}", DEFINITIONS_PRESERVED), &[QuoteOrCommand], &[], |state| {
+        RailDef::on_state("defmethod", &format!("Consumes a quote, a quote of type names as a signature, and a quoted command or string. The command runs the first quote when the values at the top of the stack match the signature. The command may have one method per signature; a previous definition is kept for values that match none of them. {}", DEFINITIONS_PRESERVED), &[Quote, Quote, QuoteOrCommand], &[], |state| {
+            let conventions = state.conventions;
+            let origin = state.origin_name();
+            Ok(state.update_stack_and_defs(|quote, mut definitions| {
+                let (name, quote) = quote.pop();
+                let (signature, quote) = quote.pop_quote("defmethod");
+                let (body, quote) = quote.pop_quote("defmethod");
+
+                let name = get_command_name(&name);
+                let signature = signature.stack.values.iter().map(get_command_name).collect::<Option<Vec<_>>>();
+                let (name, signature) = if let (Some(name), Some(signature)) = (name, signature) {
+                    (name, signature)
+                } else {
+                    log::warn(conventions, "defmethod wants a signature of type names and a string or command as a name".to_string());
+                    return (quote, definitions);
+                };
+                let signature = match types_named(&signature, &definitions) {
+                    Ok(signature) => signature,
+                    Err(t) => {
+                        log::warn(conventions, format!("The signature of {} names {}, which is not a type", name, t));
+                        return (quote, definitions);
+                    }
+                };
+
+                let def = definitions
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_else(|| RailDef::generic(&name).with_origin(&origin))
+                    .with_method(signature, body);
+                definitions.insert(name, def);
+                (quote, definitions)
+            }))
+        }),
+        RailDef::on_state_noerr("forget", "Consumes a quoted command or string, and removes its latest definition. Any definition it had shadowed becomes visible again.", &[QuoteOrCommand], &[], |state| {
             let conventions = state.conventions;
             state.update_stack_and_defs(|stack, mut definitions| {
+                let (name, stack) = stack.pop();
+                match get_command_name(&name).and_then(|name| definitions.unset(&name)) {
+                    Some(_) => {}
+                    None => log::warn(conventions, format!("Can only forget a defined command, but got {}", name)),
+                }
+                (stack, definitions)
+            })
+        }),
+        RailDef::on_state("doc", "Consumes a quoted command or string and a string. The string becomes the description of the command, as shown by describe.", &[QuoteOrCommand, String], &[], |state| {
+            let state = check_redefinition(state, 1)?;
+            let conventions = state.conventions;
+            Ok(state.update_stack_and_defs(|stack, mut definitions| {
+                let (doc, stack) = stack.pop_string("doc");
+                let (name, stack) = stack.pop();
+                let def = get_command_name(&name).and_then(|name| definitions.get(&name).cloned());
+                match def {
+                    Some(def) => definitions.replace(def.name.clone(), def.redescribe(|_| doc.clone())),
+                    None => log::warn(conventions, format!("Can only document a defined command, but got {}", name)),
+                }
+                (stack, definitions)
+            }))
+        }),
+        RailDef::on_state("immediate", "Consumes a quoted command or string, and makes the command immediate. An immediate command runs as soon as it's read, even inside a quote, with a quote of the code after it. It produces a quote of code to read instead. Since it expands wherever it's read, name an immediate command with a string rather than a quote.", &[QuoteOrCommand], &[], |state| {
+            let state = check_redefinition(state, 0)?;
+            let conventions = state.conventions;
+            Ok(state.update_stack_and_defs(|stack, mut definitions| {
+                let (name, stack) = stack.pop();
+                let def = get_command_name(&name).and_then(|name| definitions.get(&name).cloned());
+                match def {
+                    Some(def) => definitions.replace(def.name.clone(), def.as_immediate()),
+                    None => log::warn(conventions, format!("Can only make a defined command immediate, but got {}", name)),
+                }
+                (stack, definitions)
+            }))
+        }),
+        RailDef::on_state("alias", &format!("Consumes two commands, and binds the latter to the former. {}", DEFINITIONS_PRESERVED), &[QuoteOrCommand], &[], |state| {
+            let state = check_redefinition(state, 0)?;
+            let conventions = state.conventions;
+            Ok(state.update_stack_and_defs(|stack, mut definitions| {
                 let (new_name, stack) = stack.pop();
                 let (old_name, stack) = stack.pop();
                 let (new_name, old_name) = if let (Some(new_name), Some(old_name)) = (get_command_name(&new_name), get_command_name(&old_name)) {
@@ -78,60 +198,10 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                 };
 
                 (stack, definitions)
-            })
-        }),
-        RailDef::on_state_noerr("=>", "Consumes a variable number of values, and binds them as one or more commands. Quotes are not expanded.", &[Unknown, QuoteOrCommand], &[], |state| {
-            let child = state.child();
-            state.update_stack_and_defs(|stack, mut definitions| {
-                let (commands, stack) = stack.pop();
-                let commands = commands.into_command_list();
-
-                let stack = commands.into_iter().rev().fold(stack, |stack, command| {
-                    let command_name = match command {
-                        RailVal::Command(name) => name,
-                        RailVal::DeferredCommand(name) => name,
-                        _ => unreachable!()
-                    };
-
-                    let (val, stack) = stack.pop();
-
-                    definitions.insert(
-                        command_name.clone(),
-                        RailDef::from_quote(&command_name, "FIXME: Undocumented", child.clone().push(val)),
-                    );
-
-                    stack
-                });
-
-                (stack, definitions)
-            })
-        }),
-        RailDef::on_state_noerr("->", "Consumes a variable number of values, and binds them as one or more commands.", &[Unknown, QuoteOrCommand], &[], |state| {
-            let child = state.child();
-            state.update_stack_and_defs(|stack, mut definitions| {
-                let (commands, stack) = stack.pop();
-                let commands = commands.into_command_list();
-
-                let stack = commands.into_iter().rev().fold(stack, |stack, command| {
-                    let command_name = match command {
-                        RailVal::Command(name) => name,
-                        RailVal::DeferredCommand(name) => name,
-                        _ => unreachable!()
-                    };
-
-                    let (val, stack) = stack.pop();
-
-                    definitions.insert(
-                        command_name.clone(),
-                        RailDef::from_quote(&command_name, "FIXME: Undocumented", val.into_state(&child)),
-                    );
-
-                    stack
-                });
-
-                (stack, definitions)
-            })
+            }))
         }),
+        RailDef::on_state("=>", &format!("Consumes a variable number of values, and binds them as one or more commands. Quotes are not expanded. {}", DESTRUCTURING), &[Unknown, QuoteOrCommand], &[], destructure("=>", false)),
+        RailDef::on_state("->", &format!("Consumes a variable number of values, and binds them as one or more commands. {}", DESTRUCTURING), &[Unknown, QuoteOrCommand], &[], destructure("->", true)),
         RailDef::on_state_noerr("def?", "Consumes a quote or command, and produces true when it is defined, and false otherwise.", &[QuoteOrCommand], &[Boolean], |state| {
             let (name, state) = state.pop();
             let name = if let Some(name) = get_command_name(&name) {
@@ -146,6 +216,24 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             let is_def = state.definitions.contains_key(&name);
             state.push_bool(is_def)
         }),
+        RailDef::on_state_noerr("see", "Consumes a quoted command or command, and produces the Rail source of its definition as a string, or its signature if it is a builtin. The source starts with a comment saying where it was defined.", &[QuoteOrCommand], &[String], |state| {
+            let (name, state) = state.pop();
+            let name = if let Some(name) = get_command_name(&name) {
+                name
+            } else {
+                log::warn(
+                    state.conventions,
+                    format!("{} is not a string or command", name),
+                );
+                return state;
+            };
+            if let Some(def) = state.definitions.get(&name) {
+                let source = def.source();
+                state.push_string(source)
+            } else {
+                state.push_string(format!("Command \"{}\" is unknown.", &name))
+            }
+        }),
         RailDef::on_state_noerr("describe", "Consumes a quoted command or command, and produces its description as a string.", &[QuoteOrCommand], &[String], |state| {
             let (name, state) = state.pop();
             let name = if let Some(name) = get_command_name(&name) {
@@ -157,8 +245,11 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                 );
                 return state;
             };
-            if state.definitions.contains_key(&name) {
-                let description = state.definitions.get(&name).unwrap().description.clone();
+            if let Some(def) = state.definitions.get(&name) {
+                let description = match def.signature() {
+                    Some(signature) => format!("{} {}", signature, def.description),
+                    None => def.description.clone(),
+                };
                 state.push_string(description)
             } else {
                 state.push_string(format!("Command \"{}\" is unknown.", &name))
@@ -188,6 +279,104 @@ fn do_it() -> impl Fn(RailState) -> RailRunResult {
     }
 }
 
+fn destructure(name: &'static str, expand: bool) -> impl Fn(RailState) -> RailRunResult {
+    move |state| {
+        let (patterns, stack) = state.stack.clone().pop();
+        let patterns = match patterns {
+            RailVal::Quote(q) => q.stack.values.into_iter().map(name_strings).collect(),
+            pattern => vec![name_strings(pattern)],
+        };
+
+        let values = state.child().replace_stack(stack);
+        if values.len() < patterns.len() {
+            let err = RailError::StackUnderflow(
+                Box::new(values),
+                name.to_string(),
+                vec![Unknown; patterns.len()],
+            );
+            return Err(Box::new((state, err)));
+        }
+
+        match pattern::bind_top(&patterns, &values) {
+            Ok(bindings) => {
+                let stack = values.stack.values.take(values.len() - patterns.len());
+                let state = state.replace_stack(Stack::new(stack));
+                Ok(pattern::define(state, bindings, expand, name))
+            }
+            Err(value) => Err(Box::new((
+                state,
+                RailError::PatternMismatch(name.to_string(), value),
+            ))),
+        }
+    }
+}
+
+/// Check the name `depth` values below the top of the stack, as by
+/// `check_redefinition_of`, before a definition of it is made.
+fn check_redefinition(state: RailState, depth: usize) -> RailRunResult {
+    if state.len() <= depth {
+        return Ok(state);
+    }
+
+    let name = state
+        .stack
+        .values
+        .get(state.len() - 1 - depth)
+        .and_then(get_command_name);
+    match name {
+        Some(name) => check_redefinition_of(state, &name),
+        None => Ok(state),
+    }
+}
+
+/// Warn when a program's definition would override a builtin or stdlib one, or
+/// fail when redefinition is denied. Libraries being loaded may redefine freely.
+pub(crate) fn check_redefinition_of(state: RailState, name: &str) -> RailRunResult {
+    if state.origin.is_some() {
+        return Ok(state);
+    }
+
+    let origin = state
+        .definitions
+        .get(name)
+        .map(|def| def.origin.clone())
+        .filter(|origin| origin.starts_with("corelib/") || origin.starts_with("stdlib/"));
+
+    match origin {
+        Some(origin) if state.conventions.deny_redefine => Err(Box::new((
+            state,
+            RailError::Redefinition(name.to_string(), origin),
+        ))),
+        Some(origin) => {
+            log::warn(
+                state.conventions,
+                format!("{} overrides the definition from {}", name, origin),
+            );
+            Ok(state)
+        }
+        None => Ok(state),
+    }
+}
+
+/// The types named in a signature, or the first name that isn't a builtin or declared type.
+fn types_named(
+    names: &[std::string::String],
+    definitions: &Dictionary,
+) -> Result<Vec<RailType>, std::string::String> {
+    names
+        .iter()
+        .map(|name| RailType::named(name, definitions).ok_or_else(|| name.clone()))
+        .collect()
+}
+
+/// At the top level of a pattern, strings are names to bind rather than literals.
+fn name_strings(pattern: RailVal) -> RailVal {
+    match pattern {
+        RailVal::String(s) => RailVal::Command(s),
+        pattern => pattern,
+    }
+}
+
 fn doin() -> impl Fn(RailState) -> RailRunResult {
     |state| {
         let (commands, state) = state.pop();
@@ -215,7 +404,7 @@ fn doin() -> impl Fn(RailState) -> RailRunResult {
     }
 }
 
-fn get_command_name(name: &RailVal) -> Option<std::string::String> {
+pub(crate) fn get_command_name(name: &RailVal) -> Option<std::string::String> {
     match name.clone() {
         RailVal::String(s) => Some(s),
         RailVal::Command(c) => Some(c),
@@ -0,0 +1,42 @@
+use crate::v1::dtab::Dtab;
+use crate::v1::rail_machine::{RailDef, RailError, RailType};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state_noerr("dtab", "Produce a new, empty definition table. Each key holds a stack of values, where the latest value shadows the ones before it.", &[], &[Dtab], |quote| {
+            quote.push_dtab(Dtab::new())
+        }),
+        RailDef::on_state_noerr("dtab-set", "Consume a definition table, a string as a key, and a value. Produce an identical definition table where the value shadows any previous value of the key.", &[Dtab, String, A], &[Dtab], |quote| {
+            let (v, quote) = quote.pop();
+            let (k, quote) = quote.pop_string("dtab-set");
+            let (mut dtab, quote) = quote.pop_dtab("dtab-set");
+            dtab.insert(k, v);
+            quote.push_dtab(dtab)
+        }),
+        RailDef::on_state("dtab-get", "Consume a definition table and a string as a key, produce an identical definition table and the latest value of the key.", &[Dtab, String], &[Dtab, A], |quote| {
+            let (k, quote) = quote.pop_string("dtab-get");
+            let (dtab, quote) = quote.pop_dtab("dtab-get");
+            match dtab.get(&k).cloned() {
+                Some(result) => Ok(quote.push_dtab(dtab).push(result)),
+                None => Err(Box::new((quote.push_dtab(dtab).push_string(k.clone()), RailError::MissingKey("dtab-get".into(), k)))),
+            }
+        }),
+        RailDef::on_state_noerr("dtab-unset", "Consume a definition table and a string as a key. Produce an identical definition table without the latest value of the key, so that the value it shadowed is visible again.", &[Dtab, String], &[Dtab], |quote| {
+            let (k, quote) = quote.pop_string("dtab-unset");
+            let (mut dtab, quote) = quote.pop_dtab("dtab-unset");
+            dtab.unset(&k);
+            quote.push_dtab(dtab)
+        }),
+        RailDef::on_state_noerr("dtab-history", "Consume a definition table and a string as a key, produce an identical definition table and a list of every value of the key, newest first.", &[Dtab, String], &[Dtab, Quote], |quote| {
+            let (k, quote) = quote.pop_string("dtab-history");
+            let (dtab, quote) = quote.pop_dtab("dtab-history");
+            let history = dtab
+                .history(&k)
+                .into_iter()
+                .fold(quote.child(), |history, v| history.push(v.clone()));
+            quote.push_dtab(dtab).push_quote(history)
+        }),
+    ]
+}
@@ -1,8 +1,10 @@
 use std::env;
 use std::fs;
+use std::io::BufReader;
 use std::path::Path;
 
-use crate::v1::rail_machine::{RailDef, RailType};
+use crate::v1::generator;
+use crate::v1::rail_machine::{RailDef, RailError, RailType};
 
 use RailType::*;
 
@@ -53,6 +55,13 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             let contents = fs::read_to_string(path).unwrap().lines().fold(quote.child(), |quote, line| quote.push_string(line.to_owned()));
             quote.push_quote(contents)
         }),
+        RailDef::on_state("lines-of", "Consume a string as a filename, and produce a generator of that file's lines, read as they're asked for. A file that can't be opened is an error.", &[String], &[Generator], |quote| {
+            let (path, quote) = quote.pop_string("lines-of");
+            match fs::File::open(Path::new(&path)) {
+                Ok(file) => Ok(quote.push_generator(generator::Generator::lines(path, Box::new(BufReader::new(file))))),
+                Err(e) => Err(Box::new((quote.push_string(path.clone()), RailError::CantOpen(path, e.to_string())))),
+            }
+        }),
         RailDef::on_state_noerr("writef", "Consume a string as a filename and a string as file contents. The contents are written to the file.", &[String, String], &[], |quote| {
             let (path, quote) = quote.pop_string("writef");
             let (contents, quote) = quote.pop_string("writef");
@@ -0,0 +1,31 @@
+use crate::v1::generator;
+use crate::v1::rail_machine::{RailDef, RailError, RailType, RailVal};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state_noerr("generator", "Consume a quote as a starting stack and another quote as a step. Produce a generator, which makes values as they're asked for. Each step runs on the stack left by the one before, giving values with yield. A step that yields nothing ends the generator.", &[Quote, Quote], &[Generator], |state| {
+            let (step, state) = state.pop_quote("generator");
+            let (stack, state) = state.pop_quote("generator");
+            state.push_generator(generator::Generator::new(stack, step))
+        }),
+        RailDef::on_state("yield", "Consume a value and give it as the next value of the generator whose step is running. Yielding outside a generator is an error.", &[A], &[], |state| {
+            let (value, state) = state.pop();
+            match &state.yielded {
+                Some(yielded) => {
+                    yielded.borrow_mut().push_back(value);
+                    Ok(state)
+                }
+                None => Err(Box::new((state.push(value), RailError::CantYield))),
+            }
+        }),
+        RailDef::on_state("next", "Consume a generator. Produce the generator for the rest of its values, and its next value, or none when it has ended.", &[Generator], &[Generator, A], |state| {
+            let (generator, state) = state.pop_generator("next");
+            match generator.clone().next(&state) {
+                Ok((value, rest)) => Ok(state.push_generator(rest).push(value.unwrap_or(RailVal::None))),
+                Err(e) => Err(Box::new((state.push_generator(generator), e))),
+            }
+        }),
+    ]
+}
@@ -1,5 +1,7 @@
+use std::cmp::Ordering;
+
 use crate::v1::log;
-use crate::v1::rail_machine::{RailDef, RailType, RailVal};
+use crate::v1::rail_machine::{RailDef, RailError, RailType, RailVal};
 
 use RailType::*;
 
@@ -22,7 +24,45 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             "Consume a number and produce its square root.",
             |a| a.sqrt(),
         ),
-        unary_to_i64_op("floor", "Consume a number and produce its floor.", |a| a),
+        unary_to_f64_op(
+            "exp",
+            "Consume a number and produce e raised to its power.",
+            |a| a.exp(),
+        ),
+        unary_to_f64_op(
+            "ln",
+            "Consume a number and produce its natural logarithm.",
+            |a| a.ln(),
+        ),
+        unary_to_f64_op("sin", "Consume a number of radians and produce its sine.", |a| {
+            a.sin()
+        }),
+        unary_to_f64_op(
+            "cos",
+            "Consume a number of radians and produce its cosine.",
+            |a| a.cos(),
+        ),
+        unary_to_f64_op(
+            "tan",
+            "Consume a number of radians and produce its tangent.",
+            |a| a.tan(),
+        ),
+        rounding_op("floor", "Consume a number and produce its floor.", |a| {
+            a.floor()
+        }),
+        rounding_op("ceil", "Consume a number and produce its ceiling.", |a| {
+            a.ceil()
+        }),
+        rounding_op(
+            "round",
+            "Consume a number and produce the nearest integer, rounding half-way cases away from zero.",
+            |a| a.round(),
+        ),
+        rounding_op(
+            "trunc",
+            "Consume a number and produce its integer part.",
+            |a| a.trunc(),
+        ),
         binary_numeric_op(
             "+",
             "Consume two numbers and produce their sum.",
@@ -53,6 +93,122 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             |a, b| a % b,
             |a, b| a % b,
         ),
+        choosing_op(
+            "min",
+            "Consume two numbers and produce the lesser, as it was. On a tie, produce the top one.",
+            Ordering::Less,
+        ),
+        choosing_op(
+            "max",
+            "Consume two numbers and produce the greater, as it was. On a tie, produce the top one.",
+            Ordering::Greater,
+        ),
+        RailDef::on_state_noerr(
+            "pow",
+            "Consume a number as a base and a number as an exponent, and produce the base raised to the exponent. Integers raised to non-negative integer exponents produce an integer, unless it's too large for one.",
+            &[Number, Number],
+            &[Number],
+            |quote| {
+                let (b, quote) = quote.pop();
+                let (a, quote) = quote.pop();
+
+                use RailVal::*;
+                match (a, b) {
+                    (I64(a), I64(b)) => {
+                        match u32::try_from(b).ok().and_then(|e| a.checked_pow(e)) {
+                            Some(n) => quote.push_i64(n),
+                            Option::None => quote.push_f64((a as f64).powf(b as f64)),
+                        }
+                    }
+                    (I64(a), F64(b)) => quote.push_f64((a as f64).powf(b)),
+                    (F64(a), I64(b)) => quote.push_f64(a.powf(b as f64)),
+                    (F64(a), F64(b)) => quote.push_f64(a.powf(b)),
+                    (a, b) => {
+                        log::warn(
+                            quote.conventions,
+                            format!(
+                                "Can only perform pow on numeric values but got {} and {}",
+                                a, b
+                            ),
+                        );
+                        quote.push(a).push(b)
+                    }
+                }
+            },
+        ),
+        binary_to_f64_op(
+            "log",
+            "Consume a number and a number as a base, and produce the logarithm of the first in that base.",
+            |a, base| a.log(base),
+        ),
+        binary_to_f64_op(
+            "atan2",
+            "Consume two numbers as y and x coordinates, and produce the angle in radians from the positive x axis to that point.",
+            |y, x| y.atan2(x),
+        ),
+        checked_i64_op(
+            "gcd",
+            "Consume two integers and produce their greatest common divisor.",
+            |a, b| i64::try_from(gcd(a, b)).ok(),
+        ),
+        checked_i64_op(
+            "lcm",
+            "Consume two integers and produce their least common multiple.",
+            |a, b| {
+                if a == 0 || b == 0 {
+                    Some(0)
+                } else {
+                    (a.unsigned_abs() / gcd(a, b))
+                        .checked_mul(b.unsigned_abs())
+                        .and_then(|n| i64::try_from(n).ok())
+                }
+            },
+        ),
+        binary_i64_op(
+            "bit-and",
+            "Consume two integers and produce their bitwise AND.",
+            |a, b| a & b,
+        ),
+        binary_i64_op(
+            "bit-or",
+            "Consume two integers and produce their bitwise OR.",
+            |a, b| a | b,
+        ),
+        binary_i64_op(
+            "bit-xor",
+            "Consume two integers and produce their bitwise exclusive OR.",
+            |a, b| a ^ b,
+        ),
+        binary_i64_op(
+            "shl",
+            "Consume an integer and an integer as a bit count, and produce the first shifted left by that many bits.",
+            |a, b| match u32::try_from(b) {
+                Ok(b) if b < i64::BITS => a << b,
+                _ => 0,
+            },
+        ),
+        binary_i64_op(
+            "shr",
+            "Consume an integer and an integer as a bit count, and produce the first arithmetically shifted right by that many bits.",
+            |a, b| match u32::try_from(b) {
+                Ok(b) if b < i64::BITS => a >> b,
+                _ => a.signum().min(0),
+            },
+        ),
+        RailDef::on_state_noerr(
+            "pi",
+            "Produce the ratio of a circle's circumference to its diameter.",
+            &[],
+            &[F64],
+            |quote| quote.push_f64(std::f64::consts::PI),
+        ),
+        RailDef::on_state_noerr(
+            "e",
+            "Produce Euler's number, the base of the natural logarithm.",
+            &[],
+            &[F64],
+            |quote| quote.push_f64(std::f64::consts::E),
+        ),
         RailDef::on_state_noerr(
             "int-max",
             "Produce the maximum integer value.",
@@ -150,15 +306,15 @@ where
     })
 }
 
-fn unary_to_i64_op<'a, F>(name: &'a str, description: &'a str, i64_op: F) -> RailDef<'a>
+fn rounding_op<'a, F>(name: &'a str, description: &'a str, f64_op: F) -> RailDef<'a>
 where
-    F: Fn(i64) -> i64 + Sized + 'a,
+    F: Fn(f64) -> f64 + Sized + 'a,
 {
     RailDef::on_state_noerr(name, description, &[Number], &[I64], move |quote| {
         let (n, quote) = quote.pop();
         match n {
-            RailVal::I64(n) => quote.push_i64(i64_op(n)),
-            RailVal::F64(n) => quote.push_i64(i64_op(n as i64)),
+            RailVal::I64(n) => quote.push_i64(n),
+            RailVal::F64(n) => quote.push_i64(f64_op(n) as i64),
             _ => {
                 log::warn(
                     quote.conventions,
@@ -209,3 +365,134 @@ where
         },
     )
 }
+
+fn choosing_op<'a>(name: &'a str, description: &'a str, choose_second: Ordering) -> RailDef<'a> {
+    RailDef::on_state_noerr(
+        name,
+        description,
+        &[Number, Number],
+        &[Number],
+        move |quote| {
+            let (b, quote) = quote.pop();
+            let (a, quote) = quote.pop();
+
+            use RailVal::*;
+            let ordering = match (&a, &b) {
+                (I64(x), I64(y)) => x.partial_cmp(y),
+                (I64(x), F64(y)) => (*x as f64).partial_cmp(y),
+                (F64(x), I64(y)) => x.partial_cmp(&(*y as f64)),
+                (F64(x), F64(y)) => x.partial_cmp(y),
+                _ => {
+                    log::warn(
+                        quote.conventions,
+                        format!(
+                            "Can only perform {} on numeric values but got {} and {}",
+                            name, a, b
+                        ),
+                    );
+                    return quote.push(a).push(b);
+                }
+            };
+
+            if ordering == Some(choose_second) {
+                quote.push(a)
+            } else {
+                quote.push(b)
+            }
+        },
+    )
+}
+
+fn binary_to_f64_op<'a, F>(name: &'a str, description: &'a str, f64_op: F) -> RailDef<'a>
+where
+    F: Fn(f64, f64) -> f64 + Sized + 'a,
+{
+    RailDef::on_state_noerr(name, description, &[Number, Number], &[F64], move |quote| {
+        let (b, quote) = quote.pop();
+        let (a, quote) = quote.pop();
+
+        use RailVal::*;
+        match (a, b) {
+            (I64(a), I64(b)) => quote.push_f64(f64_op(a as f64, b as f64)),
+            (I64(a), F64(b)) => quote.push_f64(f64_op(a as f64, b)),
+            (F64(a), I64(b)) => quote.push_f64(f64_op(a, b as f64)),
+            (F64(a), F64(b)) => quote.push_f64(f64_op(a, b)),
+            (a, b) => {
+                log::warn(
+                    quote.conventions,
+                    format!(
+                        "Can only perform {} on numeric values but got {} and {}",
+                        name, a, b
+                    ),
+                );
+                quote.push(a).push(b)
+            }
+        }
+    })
+}
+
+fn binary_i64_op<'a, F>(name: &'a str, description: &'a str, i64_op: F) -> RailDef<'a>
+where
+    F: Fn(i64, i64) -> i64 + Sized + 'a,
+{
+    RailDef::on_state_noerr(name, description, &[I64, I64], &[I64], move |quote| {
+        let (b, quote) = quote.pop();
+        let (a, quote) = quote.pop();
+
+        match (a, b) {
+            (RailVal::I64(a), RailVal::I64(b)) => quote.push_i64(i64_op(a, b)),
+            (a, b) => {
+                log::warn(
+                    quote.conventions,
+                    format!(
+                        "Can only perform {} on integer values but got {} and {}",
+                        name, a, b
+                    ),
+                );
+                quote.push(a).push(b)
+            }
+        }
+    })
+}
+
+/// Like `binary_i64_op`, but the operation produces nothing when the result
+/// doesn't fit in an integer, which is an error.
+fn checked_i64_op<'a, F>(name: &'a str, description: &'a str, i64_op: F) -> RailDef<'a>
+where
+    F: Fn(i64, i64) -> Option<i64> + Sized + 'a,
+{
+    RailDef::on_state(name, description, &[I64, I64], &[I64], move |quote| {
+        let (b, quote) = quote.pop();
+        let (a, quote) = quote.pop();
+
+        match (a, b) {
+            (RailVal::I64(a), RailVal::I64(b)) => match i64_op(a, b) {
+                Some(n) => Ok(quote.push_i64(n)),
+                None => Err(Box::new((
+                    quote.push_i64(a).push_i64(b),
+                    RailError::Overflow(name.to_string()),
+                ))),
+            },
+            (a, b) => {
+                log::warn(
+                    quote.conventions,
+                    format!(
+                        "Can only perform {} on integer values but got {} and {}",
+                        name, a, b
+                    ),
+                );
+                Ok(quote.push(a).push(b))
+            }
+        }
+    })
+}
+
+/// The greatest common divisor, unsigned since the one of the smallest integer
+/// and zero is too large for an i64.
+fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
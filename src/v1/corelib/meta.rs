@@ -1,4 +1,5 @@
 use crate::v1::{
+    docs,
     rail_machine::{Context, RailDef, RailType},
     RAIL_VERSION,
 };
@@ -33,6 +34,21 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                 state.push_quote(defs)
             },
         ),
+        RailDef::on_state_noerr(
+            "apropos",
+            "Consume a string, and produce a list of the commands whose name or description contains it, ignoring case.",
+            &[String],
+            &[Quote],
+            |state| {
+                let (term, state) = state.pop_string("apropos");
+
+                let names = docs::apropos(&state.definitions, &term)
+                    .iter()
+                    .fold(state.child(), |quote, name| quote.push_str(name));
+
+                state.push_quote(names)
+            },
+        ),
         // TODO: In typing, consumes of 'quote-all' should be something that means 0-to-many
         RailDef::on_state_noerr(
             "quote-all",
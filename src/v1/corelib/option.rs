@@ -0,0 +1,35 @@
+use crate::v1::rail_machine::{RailDef, RailType, RailVal};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state_noerr("none", "Produce the none value, which stands in for an absent result.", &[], &[Nothing], |quote| {
+            quote.push_none()
+        }),
+        RailDef::on_state_noerr("none?", "Consume a value. Produce true if it is none, and false otherwise.", &[A], &[Boolean], |quote| {
+            let (a, quote) = quote.pop();
+            quote.push_bool(matches!(a, RailVal::None))
+        }),
+        RailDef::on_state_noerr("some?", "Consume a value. Produce true if it is not none, and false otherwise.", &[A], &[Boolean], |quote| {
+            let (a, quote) = quote.pop();
+            quote.push_bool(!matches!(a, RailVal::None))
+        }),
+        RailDef::on_state_noerr("or-default", "Consume a value and a default value. Produce the value, or the default if the value is none.", &[A, A], &[A], |quote| {
+            let (default, quote) = quote.pop();
+            let (a, quote) = quote.pop();
+            match a {
+                RailVal::None => quote.push(default),
+                a => quote.push(a),
+            }
+        }),
+        RailDef::on_jailed_state("maybe", "Consume a value and a quote. If the value is none, produce none. Otherwise, execute the quote with the value on top of the stack. Any definitions are local only to the quote.", &[A, Quote], &[A], |state| {
+            let (action, state) = state.pop_quote("maybe");
+            let (a, state) = state.pop();
+            match a {
+                RailVal::None => Ok(state.push_none()),
+                a => action.run_in_state(state.push(a)),
+            }
+        }),
+    ]
+}
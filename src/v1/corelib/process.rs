@@ -1,5 +1,6 @@
 use std::env;
 
+use crate::v1::generator;
 use crate::v1::rail_machine::{self, RailDef, RailType, RailVal};
 
 use RailType::*;
@@ -47,10 +48,10 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             });
             quote.push_stab(vars)
         }),
-        RailDef::on_state_noerr("envget", "Consume a string as an environment key, and produce its value.", &[String], &[String], |quote| {
+        RailDef::on_state_noerr("envget", "Consume a string as an environment key, and produce its value, or none if it is unset.", &[String], &[A], |quote| {
             let (key, quote) = quote.pop_string("envget");
-            let var = env::var(key).unwrap_or_else(|_| "unset".to_string());
-            quote.push_string(var)
+            let var = env::var(key).ok().map(RailVal::String);
+            quote.push_optional(var)
         }),
         RailDef::on_state_noerr("envset", "Consume a string as an environment key and a string as its value, and set it in the current environment.", &[String, String], &[], |quote| {
             let (var, quote) = quote.pop_string("envset");
@@ -58,12 +59,16 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             env::set_var(key, var);
             quote
         }),
-        RailDef::on_state_noerr("stdin", "Read standard input and produce a list of lines.", &[], &[Quote], |quote| {
+        RailDef::on_state_noerr("stdin", "Read standard input and produce a list of lines. To handle lines as they arrive instead, use stdin-lines.", &[], &[Quote], |quote| {
+            // Stop at the first unreadable line, as stdin-lines does. Skipping errors can spin forever on a handle that keeps failing.
             let lines = std::io::stdin()
                 .lines()
-                .filter_map(|line| line.ok())
+                .map_while(Result::ok)
                 .fold(quote.child(), |quote, line| quote.push_string(line));
             quote.push_quote(lines)
         }),
+        RailDef::on_state_noerr("stdin-lines", "Produce a generator of the lines of standard input, read as they're asked for.", &[], &[Generator], |quote| {
+            quote.push_generator(generator::Generator::stdin_lines())
+        }),
     ]
 }
@@ -0,0 +1,64 @@
+use crate::v1::log;
+use crate::v1::rail_machine::{RailDef, RailError, RailType};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state_noerr("seed", "Consume an integer and use it to seed the random number generator. The same seed always produces the same sequence of random values.", &[I64], &[], |quote| {
+            let (seed, quote) = quote.pop_i64("seed");
+            quote.rng.reseed(seed as u64);
+            quote
+        }),
+        RailDef::on_state_noerr("rand-i64", "Produce a random integer.", &[], &[I64], |quote| {
+            let n = quote.rng.next_u64() as i64;
+            quote.push_i64(n)
+        }),
+        RailDef::on_state_noerr("rand-f64", "Produce a random floating-point number between 0 (inclusive) and 1 (exclusive).", &[], &[F64], |quote| {
+            let n = quote.rng.next_f64();
+            quote.push_f64(n)
+        }),
+        RailDef::on_state_noerr("rand-range", "Consume an integer as a lower bound and an integer as an upper bound, and produce a random integer from the lower bound (inclusive) to the upper bound (exclusive).", &[I64, I64], &[I64], |quote| {
+            let (high, quote) = quote.pop_i64("rand-range");
+            let (low, quote) = quote.pop_i64("rand-range");
+
+            if high <= low {
+                log::warn(
+                    quote.conventions,
+                    format!("Can only perform rand-range when the upper bound is greater than the lower bound, but got {} and {}", low, high),
+                );
+                return quote.push_i64(low).push_i64(high);
+            }
+
+            let span = high.wrapping_sub(low) as u64;
+            let n = low.wrapping_add(quote.rng.below(span) as i64);
+            quote.push_i64(n)
+        }),
+        RailDef::on_state_noerr("shuffle", "Consume a quote, and produce a quote with the same values in a random order.", &[Quote], &[Quote], |quote| {
+            let (sequence, quote) = quote.pop_quote("shuffle");
+            let mut values = sequence.stack.values.clone();
+
+            // Fisher-Yates
+            for i in (1..values.len()).rev() {
+                let j = quote.rng.below(i as u64 + 1) as usize;
+                values.swap(i, j);
+            }
+
+            let shuffled = values
+                .into_iter()
+                .fold(quote.child(), |shuffled, value| shuffled.push(value));
+            quote.push_quote(shuffled)
+        }),
+        RailDef::on_state("choice", "Consume a non-empty quote, and produce one of its values chosen at random.", &[Quote], &[A], |quote| {
+            let (sequence, quote) = quote.pop_quote("choice");
+
+            if sequence.is_empty() {
+                return Err(Box::new((quote.push_quote(sequence), RailError::EmptyCollection("choice".into()))));
+            }
+
+            let i = quote.rng.below(sequence.len() as u64) as usize;
+            let value = sequence.stack.values[i].clone();
+            Ok(quote.push(value))
+        }),
+    ]
+}
@@ -0,0 +1,269 @@
+use crate::v1::corelib::command::{check_redefinition_of, get_command_name};
+use crate::v1::pattern;
+use crate::v1::rail_machine::{
+    Dictionary, RailDef, RailError, RailRunResult, RailState, RailType, RailVal, Record,
+};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state("record", "Consumes a quote of field names and a string as a type name. Defines a constructor named after the type that consumes one value per field, a predicate like point?, and an accessor per field like point-x. Any new definitions are preserved in the calling context.", &[Quote, String], &[], |state| {
+            let (type_name, state) = state.pop_string("record");
+            let (fields, state) = state.pop_quote("record");
+
+            let field_names = match names_of(&fields) {
+                Some(names) => names,
+                None => {
+                    let err = RailError::TypeMismatch(vec![Quote, String], vec![RailVal::Quote(fields.clone()), RailVal::String(type_name.clone())]);
+                    return Err(Box::new((state.push_quote(fields).push_string(type_name), err)));
+                }
+            };
+
+            let declared = declare(&state.origin_name(), &type_name, vec![(type_name.clone(), field_names)]);
+            define_all(state, declared).map_err(|failure| {
+                let (state, e) = *failure;
+                Box::new((state.push_quote(fields).push_string(type_name), e))
+            })
+        }),
+        RailDef::on_state("variants", "Consumes a quote of variants and a string as a type name. Each variant is a quote of its name followed by its field names. Defines a constructor, a predicate and accessors for each variant, and a predicate for the type itself. Any new definitions are preserved in the calling context.", &[Quote, String], &[], |state| {
+            let (type_name, state) = state.pop_string("variants");
+            let (variants, state) = state.pop_quote("variants");
+
+            let declared = variants
+                .stack
+                .values
+                .iter()
+                .map(|variant| match variant {
+                    RailVal::Quote(q) => names_of(q).and_then(|names| names.split_first().map(|(name, fields)| (name.clone(), fields.to_vec()))),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+
+            let declared = match declared {
+                Some(declared) => declared,
+                None => {
+                    let err = RailError::TypeMismatch(vec![Quote, String], vec![RailVal::Quote(variants.clone()), RailVal::String(type_name.clone())]);
+                    return Err(Box::new((state.push_quote(variants).push_string(type_name), err)));
+                }
+            };
+
+            let declared = declare(&state.origin_name(), &type_name, declared);
+            define_all(state, declared).map_err(|failure| {
+                let (state, e) = *failure;
+                Box::new((state.push_quote(variants).push_string(type_name), e))
+            })
+        }),
+        RailDef::on_state("match", "Consumes a value and a quote of pattern + action pairs. Runs the action of the first pattern that matches the value. A variant name matches values of that variant and pushes their fields. A builtin or declared type name matches values of that type and pushes the value, as does the wildcard _. Any other pattern takes the value apart like ->, binding its names for the action, and strings and other literals match equal values.", &[A, Quote], &[Unknown], |state| {
+            let (cases, state) = state.pop_quote("match");
+            let (value, state) = state.pop();
+
+            for case in cases.stack.values.iter().collect::<Vec<_>>().chunks(2) {
+                let (pattern, action) = match case {
+                    [pattern, RailVal::Quote(action)] => (*pattern, action),
+                    _ => {
+                        let err = RailError::TypeMismatch(vec![Quote, Quote], case.iter().map(|v| (*v).clone()).collect());
+                        return Err(Box::new((state.push(value).push_quote(cases.clone()), err)));
+                    }
+                };
+
+                let pattern = match pattern {
+                    RailVal::Quote(q) if q.len() == 1 => &q.stack.values[0],
+                    pattern => pattern,
+                };
+
+                match kind_of_pattern(pattern, &state.definitions) {
+                    Some(Kind::Variant(type_name, name)) => match &value {
+                        RailVal::Record(record) if record.is(&type_name, &name) => {
+                            let state = record.fields.iter().fold(state, |state, (_, v)| state.push(v.clone()));
+                            return action.clone().run_in_state(state);
+                        }
+                        _ => continue,
+                    },
+                    Some(Kind::Type(t)) if t.accepts(&value) => return action.clone().run_in_state(state.push(value)),
+                    Some(Kind::Type(_)) => continue,
+                    None => {}
+                }
+
+                let mut bindings = vec![];
+                if pattern::bind(pattern, &value, &mut bindings) {
+                    return action.clone().run_in_state(pattern::define(state, bindings, true, "match"));
+                }
+            }
+
+            Err(Box::new((state.push(value.clone()).push_quote(cases), RailError::PatternMismatch("match".into(), Box::new(value)))))
+        }),
+    ]
+}
+
+enum Kind {
+    Variant(std::string::String, std::string::String),
+    Type(RailType),
+}
+
+/// Whether a match pattern names a variant or a type. Only commands can, and
+/// only when they name a declared variant, or a type other than a placeholder
+/// like `a`; `_` is the type of everything. Other patterns bind or compare.
+fn kind_of_pattern(pattern: &RailVal, definitions: &Dictionary) -> Option<Kind> {
+    let name = match pattern {
+        RailVal::Command(name) => name,
+        _ => return None,
+    };
+    if name == "_" {
+        return Some(Kind::Type(A));
+    }
+
+    if let Some([User(type_name)]) = definitions.get(name).map(|def| def.produces()) {
+        return Some(Kind::Variant(type_name.clone(), name.clone()));
+    }
+
+    match RailType::named(name, definitions)? {
+        A | B | C | Unknown => None,
+        t => Some(Kind::Type(t)),
+    }
+}
+
+fn names_of(quote: &RailState) -> Option<Vec<std::string::String>> {
+    quote.stack.values.iter().map(get_command_name).collect()
+}
+
+/// Define each of the definitions, as long as none overrides a builtin or
+/// stdlib one when that is denied.
+fn define_all(state: RailState, defs: Vec<RailDef<'static>>) -> RailRunResult {
+    let state = defs
+        .iter()
+        .try_fold(state, |state, def| check_redefinition_of(state, &def.name))?;
+
+    let mut definitions = state.definitions.clone();
+    for def in defs {
+        definitions.insert(def.name.clone(), def);
+    }
+    Ok(state.replace_definitions(definitions))
+}
+
+/// The definitions for a declared type: a predicate for the type, and a
+/// constructor, accessors and (for sum types) a predicate for each variant.
+fn declare(
+    origin: &str,
+    type_name: &str,
+    variants: Vec<(std::string::String, Vec<std::string::String>)>,
+) -> Vec<RailDef<'static>> {
+    let mut definitions = vec![];
+    let is_type = {
+        let type_name = type_name.to_string();
+        move |value: &RailVal| matches!(value, RailVal::Record(r) if r.type_name == type_name)
+    };
+    let type_predicate = predicate(
+        &format!("{}?", type_name),
+        &format!(
+            "Consumes a value. Produces true if it is a {}, and false otherwise.",
+            type_name
+        ),
+        is_type,
+    );
+    definitions.push(type_predicate.with_origin(origin));
+
+    for (variant, fields) in variants {
+        let user_type = User(type_name.to_string());
+
+        if variant != type_name {
+            let is_variant = {
+                let type_name = type_name.to_string();
+                let variant = variant.clone();
+                move |value: &RailVal| matches!(value, RailVal::Record(r) if r.is(&type_name, &variant))
+            };
+            let variant_predicate = predicate(
+                &format!("{}?", variant),
+                &format!(
+                    "Consumes a value. Produces true if it is a {} {}, and false otherwise.",
+                    type_name, variant
+                ),
+                is_variant,
+            );
+            definitions.push(variant_predicate.with_origin(origin));
+        }
+
+        for field in fields.iter() {
+            let accessor = accessor(type_name, &variant, field);
+            definitions.push(accessor.with_origin(origin));
+        }
+
+        let constructor = RailDef::on_state_noerr(
+            &variant,
+            &format!(
+                "Consumes {} value(s) as the fields [ {} ] and produces a {}.",
+                fields.len(),
+                fields.join(" "),
+                type_name
+            ),
+            &vec![A; fields.len()],
+            &[user_type],
+            {
+                let type_name = type_name.to_string();
+                let variant = variant.clone();
+                move |state| {
+                    let (values, state) =
+                        fields
+                            .iter()
+                            .fold((vec![], state), |(mut values, state), _| {
+                                let (value, state) = state.pop();
+                                values.push(value);
+                                (values, state)
+                            });
+
+                    let fields = fields
+                        .iter()
+                        .cloned()
+                        .zip(values.into_iter().rev())
+                        .collect();
+                    state.push_record(Record {
+                        type_name: type_name.clone(),
+                        variant: variant.clone(),
+                        fields,
+                    })
+                }
+            },
+        );
+        definitions.push(constructor.with_origin(origin));
+    }
+
+    definitions
+}
+
+fn predicate(
+    name: &str,
+    description: &str,
+    test: impl Fn(&RailVal) -> bool + 'static,
+) -> RailDef<'static> {
+    RailDef::on_state_noerr(name, description, &[A], &[Boolean], move |state| {
+        let (value, state) = state.pop();
+        state.push_bool(test(&value))
+    })
+}
+
+fn accessor(type_name: &str, variant: &str, field: &str) -> RailDef<'static> {
+    let name = format!("{}-{}", variant, field);
+    let consumes = [User(type_name.to_string())];
+    let user_type = consumes[0].clone();
+    let description = format!(
+        "Consumes a {} {}, and produces its {} field.",
+        type_name, variant, field
+    );
+    let type_name = type_name.to_string();
+    let variant = variant.to_string();
+    let field = field.to_string();
+
+    RailDef::on_state(&name, &description, &consumes, &[A], move |state| {
+        let (value, state) = state.pop();
+        match &value {
+            RailVal::Record(r) if r.is(&type_name, &variant) => {
+                let found = r.get(&field).cloned().unwrap();
+                Ok(state.push(found))
+            }
+            _ => Err(Box::new((
+                state.push(value.clone()),
+                RailError::TypeMismatch(vec![user_type.clone()], vec![value]),
+            ))),
+        }
+    })
+}
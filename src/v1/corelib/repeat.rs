@@ -12,12 +12,7 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             let (n, stack) = state.stack.clone().pop_i64("times");
             let (commands, stack) = stack.pop_quote("times");
             let state = state.replace_stack(stack);
-            (0..n).fold(Ok(state), |state, _n| {
-                match state {
-                    Ok(state) => commands.clone().jailed_run_in_state(state),
-                    err => err,
-                }
-            })
+            (0..n).try_fold(state, |state, _n| commands.clone().jailed_run_in_state(state))
         },
     )]
 }
@@ -1,8 +1,14 @@
+use im::Vector;
+
 use crate::v1::log;
-use crate::v1::rail_machine::{RailDef, RailError, RailType, RailVal, Stack};
+use crate::v1::rail_machine::{
+    self, RailDef, RailError, RailFailure, RailRunResult, RailState, RailType, RailVal, Stack,
+};
 
 use RailType::*;
 
+const ANY: &str = "Consume one quote as a list and another quote as a predicate. Produce true if the predicate is true for any value in the list, and false otherwise.";
+
 // TODO: These should all work for both String and Quote? Should String also be a Quote? Typeclasses?
 pub fn builtins() -> Vec<RailDef<'static>> {
     vec![
@@ -51,6 +57,9 @@ pub fn builtins() -> Vec<RailDef<'static>> {
         }),
         RailDef::on_state("pop", "Consume a quote, and produce a quote (with the last element removed) and the quote's last element.", &[Quote], &[Quote, A], |quote| {
             let (sequence, quote) = quote.pop_quote("pop");
+            if sequence.is_empty() {
+                return Err(Box::new((quote.push_quote(sequence), RailError::EmptyCollection("pop".into()))));
+            }
             let (a, sequence) = sequence.pop();
             Ok(quote.push_quote(sequence).push(a))
         }),
@@ -64,12 +73,24 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             let (nth, state) = state.pop_i64("nth");
             let (seq, state) = state.pop_quote("nth");
 
-            let nth = seq.stack.values.get(nth as usize).unwrap();
-
-            Ok(state.push(nth.clone()))
+            match get_nth(&seq, nth) {
+                Some(value) => Ok(state.push(value)),
+                None => {
+                    let len = seq.len();
+                    Err(Box::new((state.push_quote(seq).push_i64(nth), RailError::IndexOutOfBounds("nth".into(), nth, len))))
+                }
+            }
+        }),
+        RailDef::on_state_noerr("nth?", "Consume a quote and an integer, and produce the element at the 0-indexed location specified, or none if there is no such element.", &[Quote, I64], &[A], |state| {
+            let (nth, state) = state.pop_i64("nth?");
+            let (seq, state) = state.pop_quote("nth?");
+            state.push_optional(get_nth(&seq, nth))
         }),
         RailDef::on_state("deq", "Consume a quote, and produce its first value and the quote with the first value removed.", &[Quote], &[A, Quote], |quote| {
-            let (sequence, quote) = quote.pop_quote("pop");
+            let (sequence, quote) = quote.pop_quote("deq");
+            if sequence.is_empty() {
+                return Err(Box::new((quote.push_quote(sequence), RailError::EmptyCollection("deq".into()))));
+            }
             let (a, sequence) = sequence.dequeue();
             Ok(quote.push(a).push_quote(sequence))
         }),
@@ -79,7 +100,7 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                 RailVal::String(s) => quote.push_string(s.chars().rev().collect()),
                 RailVal::Quote(q) => quote.push_quote(q.reverse()),
                 _ => {
-                    return Err((quote.push(a.clone()), RailError::TypeMismatch(vec![QuoteOrString], vec![a])));
+                    return Err(Box::new((quote.push(a.clone()), RailError::TypeMismatch(vec![QuoteOrString], vec![a]))));
                 }
             };
             Ok(reversed)
@@ -102,14 +123,21 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                         quote.conventions,
                         format!("Can only perform concat when previous two values are both strings or both quotes. Instead got {} and {}", prefix, suffix),
                     );
-                    return Err((quote.push(prefix.clone()).push(suffix.clone()), RailError::TypeMismatch(vec![QuoteOrString, QuoteOrString], vec![prefix, suffix])));
+                    return Err(Box::new((quote.push(prefix.clone()).push(suffix.clone()), RailError::TypeMismatch(vec![QuoteOrString, QuoteOrString], vec![prefix, suffix]))));
                 }
             };
             Ok(quote)
         }),
-        RailDef::on_state("filter", "Consume one quote as a list and another quote as a predicate, produce a list of all values from the original list that return true for the predicate.", &[Quote, Quote], &[Quote], |state| {
+        RailDef::on_state("filter", "Consume one quote as a list and another quote as a predicate, produce a list of all values from the original list that return true for the predicate. Given a generator instead of a list, produce a generator that tests each value as it's asked for.", &[QuoteOrGenerator, Quote], &[QuoteOrGenerator], |state| {
             let (predicate, state) = state.pop_quote("filter");
-            let (sequence, state) = state.pop_quote("filter");
+            let (sequence, state) = state.pop();
+
+            let sequence = match sequence {
+                RailVal::Generator(generator) => return Ok(state.push_generator(generator.filter(predicate))),
+                RailVal::Quote(sequence) => sequence,
+                value => return Err(Box::new((state.push(value.clone()).push_quote(predicate.clone()), RailError::TypeMismatch(vec![QuoteOrGenerator, Quote], vec![value, RailVal::Quote(predicate)])))),
+            };
+
             let mut results = state.child();
 
             for term in sequence.stack.values {
@@ -123,9 +151,15 @@ pub fn builtins() -> Vec<RailDef<'static>> {
 
             Ok(state.push_quote(results))
         }),
-        RailDef::on_state("map", "Consume one quote as a list and another quote as a transform, produce a list of all values from the original list after applying the transformation.", &[Quote, Quote], &[Quote], |state| {
+        RailDef::on_state("map", "Consume one quote as a list and another quote as a transform, produce a list of all values from the original list after applying the transformation. Given a generator instead of a list, produce a generator that applies the transformation to each value as it's asked for.", &[QuoteOrGenerator, Quote], &[QuoteOrGenerator], |state| {
             let (transform, state) = state.pop_quote("map");
-            let (sequence, state) = state.pop_quote("map");
+            let (sequence, state) = state.pop();
+
+            let sequence = match sequence {
+                RailVal::Generator(generator) => return Ok(state.push_generator(generator.map(transform))),
+                RailVal::Quote(sequence) => sequence,
+                value => return Err(Box::new((state.push(value.clone()).push_quote(transform.clone()), RailError::TypeMismatch(vec![QuoteOrGenerator, Quote], vec![value, RailVal::Quote(transform)])))),
+            };
 
             let mut results = state.child();
 
@@ -138,36 +172,69 @@ pub fn builtins() -> Vec<RailDef<'static>> {
 
             Ok(state.push_quote(results))
         }),
-        RailDef::on_state("each!", "Consume one quote as a list and another quote as commands. Run the commands on each list, any definitions will be preserved in the calling context.", &[Quote, Quote], &[Unknown], |state| {
+        RailDef::on_state("each!", "Consume one quote as a list and another quote as commands. Run the commands on each list, any definitions will be preserved in the calling context. Given a generator instead of a list, run the commands on each of its values until it ends.", &[QuoteOrGenerator, Quote], &[Unknown], |state| {
             let (command, state) = state.pop_quote("each!");
-            let (sequence, state) = state.pop_quote("each!");
+            let (sequence, state) = state.pop();
+
+            let sequence = match sequence {
+                RailVal::Generator(generator) => {
+                    return generator.try_fold(state, |state, value| command.clone().run_in_state(state.push(value)));
+                }
+                RailVal::Quote(sequence) => sequence,
+                value => return Err(Box::new((state.push(value.clone()).push_quote(command.clone()), RailError::TypeMismatch(vec![QuoteOrGenerator, Quote], vec![value, RailVal::Quote(command)])))),
+            };
 
             sequence
                 .stack
                 .values
                 .into_iter()
-                .fold(Ok(state), |state, value| {
-                    let state = state?.update_stack(|quote| quote.push(value.clone()));
+                .try_fold(state, |state, value| {
+                    let state = state.update_stack(|quote| quote.push(value.clone()));
                     command.clone().run_in_state(state)
                 })
         }),
-        RailDef::on_jailed_state("each", "Consume one quote as a list and another quote as commands. Run the commands on each list, any definitions will NOT be preserved in the calling context.", &[Quote, Quote], &[Unknown], |state| {
+        RailDef::on_jailed_state("each", "Consume one quote as a list and another quote as commands. Run the commands on each list, any definitions will NOT be preserved in the calling context. Given a generator instead of a list, run the commands on each of its values until it ends.", &[QuoteOrGenerator, Quote], &[Unknown], |state| {
             let (command, state) = state.pop_quote("each");
-            let (sequence, state) = state.pop_quote("each");
+            let (sequence, state) = state.pop();
 
             let definitions = state.definitions.clone();
 
+            let sequence = match sequence {
+                RailVal::Generator(generator) => {
+                    return generator.try_fold(state, |state, value| {
+                        let state = state.push(value).replace_definitions(definitions.clone());
+                        command.clone().jailed_run_in_state(state)
+                    });
+                }
+                RailVal::Quote(sequence) => sequence,
+                value => return Err(Box::new((state.push(value.clone()).push_quote(command.clone()), RailError::TypeMismatch(vec![QuoteOrGenerator, Quote], vec![value, RailVal::Quote(command)])))),
+            };
+
             sequence
                 .stack
                 .values
                 .into_iter()
-                .fold(Ok(state), |state, value| {
-                    let state = state?
+                .try_fold(state, |state, value| {
+                    let state = state
                         .update_stack(|quote| quote.push(value.clone()))
                         .replace_definitions(definitions.clone());
                     command.clone().jailed_run_in_state(state)
                 })
         }),
+        RailDef::on_state("collect", "Consume a generator, and produce a quote of all its values. Given a quote, produce it as it is.", &[QuoteOrGenerator], &[Quote], |state| {
+            let (sequence, state) = state.pop();
+            match sequence {
+                RailVal::Generator(generator) => {
+                    let values = generator.clone().try_fold(state.child(), |values, value| Ok(values.push(value)));
+                    match values {
+                        Ok(values) => Ok(state.push_quote(values)),
+                        Err(failure) => Err(Box::new((state.push_generator(generator), failure.1))),
+                    }
+                }
+                RailVal::Quote(sequence) => Ok(state.push_quote(sequence)),
+                value => Err(Box::new((state.push(value.clone()), RailError::TypeMismatch(vec![QuoteOrGenerator], vec![value])))),
+            }
+        }),
         RailDef::on_state_noerr("zip", "Consume two quotes as lists, and produce a list of pairs of values. The result as short as the shortest list; additional values from a longer list will be discarded.", &[Quote, Quote], &[Quote], |state| {
             let (b, state) = state.pop_quote("zip");
             let (a, state) = state.pop_quote("zip");
@@ -200,12 +267,346 @@ pub fn builtins() -> Vec<RailDef<'static>> {
                     .map(|(a, b)| state.child().push(a).push(b))
                     .map(|ab| match xform.clone().run_in_state(ab) {
                         Ok(c) => c,
-                        Err((c, _)) => c,
+                        Err(failure) => failure.0,
                     })
                     .fold(state.child(), |c, result| c.push_quote(result));
 
                 Ok(state.push_quote(c))
             },
         ),
+        RailDef::on_state("fold", "Consume one quote as a list, a value as an initial accumulator, and another quote as a combining function. The function is given the accumulator and each value in turn, and the final accumulator is produced.", &[Quote, A, Quote], &[A], |state| {
+            let (combine, state) = state.pop_quote("fold");
+            let (init, state) = state.pop();
+            let (sequence, state) = state.pop_quote("fold");
+
+            let acc = sequence.stack.values.into_iter().try_fold(init, |acc, term| {
+                combine_values(&state, &combine, acc, term)
+            })?;
+
+            Ok(state.push(acc))
+        }),
+        RailDef::on_state("reduce", "Consume one quote as a non-empty list and another quote as a combining function. Like fold, but the first value of the list is the initial accumulator.", &[Quote, Quote], &[A], |state| {
+            let (combine, state) = state.pop_quote("reduce");
+            let (sequence, state) = state.pop_quote("reduce");
+
+            if sequence.is_empty() {
+                return Err(Box::new((state.push_quote(sequence).push_quote(combine), RailError::EmptyCollection("reduce".into()))));
+            }
+
+            let (init, rest) = sequence.dequeue();
+            let acc = rest.stack.values.into_iter().try_fold(init, |acc, term| {
+                combine_values(&state, &combine, acc, term)
+            })?;
+
+            Ok(state.push(acc))
+        }),
+        RailDef::on_state("scan", "Consume one quote as a list, a value as an initial accumulator, and another quote as a combining function. Like fold, but produces a list of every intermediate accumulator.", &[Quote, A, Quote], &[Quote], |state| {
+            let (combine, state) = state.pop_quote("scan");
+            let (init, state) = state.pop();
+            let (sequence, state) = state.pop_quote("scan");
+
+            let mut results = state.child();
+            let mut acc = init;
+
+            for term in sequence.stack.values {
+                acc = combine_values(&state, &combine, acc, term)?;
+                results = results.push(acc.clone());
+            }
+
+            Ok(state.push_quote(results))
+        }),
+        RailDef::on_state("flat-map", "Consume one quote as a list and another quote as a transform that produces a list. Produce the concatenation of the lists produced for each value in the original list.", &[Quote, Quote], &[Quote], |state| {
+            let (transform, state) = state.pop_quote("flat-map");
+            let (sequence, state) = state.pop_quote("flat-map");
+
+            let mut results = state.child();
+
+            for term in sequence.stack.values {
+                let substate = state.child().replace_stack(Stack::of(term));
+                let substate = transform.clone().jailed_run_in_state(substate)?;
+                let (produced, _) = substate.stack.pop_quote("flat-map");
+                for value in produced.stack.values {
+                    results = results.push(value);
+                }
+            }
+
+            Ok(state.push_quote(results))
+        }),
+        RailDef::on_state("take", "Consume a quote and an integer n, and produce a quote of the first n values. Given a generator instead of a quote, produce a generator that ends after its first n values.", &[QuoteOrGenerator, I64], &[QuoteOrGenerator], |state| {
+            let (n, state) = state.pop_i64("take");
+            let (sequence, state) = state.pop();
+
+            let sequence = match sequence {
+                RailVal::Generator(generator) => return Ok(state.push_generator(generator.take(n.max(0) as usize))),
+                RailVal::Quote(sequence) => sequence,
+                value => return Err(Box::new((state.push(value.clone()).push_i64(n), RailError::TypeMismatch(vec![QuoteOrGenerator, I64], vec![value, RailVal::I64(n)])))),
+            };
+
+            let values = sequence.stack.values.take(clamp_index(n, sequence.len()));
+            let values = quote_of(&state, values);
+            Ok(state.push_quote(values))
+        }),
+        RailDef::on_state_noerr("drop-first", "Consume a quote and an integer n, and produce a quote of all but the first n values.", &[Quote, I64], &[Quote], |state| {
+            let (n, state) = state.pop_i64("drop-first");
+            let (sequence, state) = state.pop_quote("drop-first");
+            let values = sequence.stack.values.skip(clamp_index(n, sequence.len()));
+            let values = quote_of(&state, values);
+            state.push_quote(values)
+        }),
+        RailDef::on_state_noerr("slice", "Consume a quote, an integer as a start index, and an integer as an end index. Produce a quote of the values from the start (inclusive) to the end (exclusive).", &[Quote, I64, I64], &[Quote], |state| {
+            let (end, state) = state.pop_i64("slice");
+            let (start, state) = state.pop_i64("slice");
+            let (sequence, state) = state.pop_quote("slice");
+
+            let len = sequence.len();
+            let end = clamp_index(end, len);
+            let start = clamp_index(start, len).min(end);
+            let values = sequence.stack.values.clone().slice(start..end);
+
+            let values = quote_of(&state, values);
+            state.push_quote(values)
+        }),
+        RailDef::on_state("take-while", "Consume one quote as a list and another quote as a predicate. Produce a quote of the leading values for which the predicate is true.", &[Quote, Quote], &[Quote], |state| {
+            let (predicate, state) = state.pop_quote("take-while");
+            let (sequence, state) = state.pop_quote("take-while");
+
+            let n = count_while(&state, &predicate, &sequence, "take-while")?;
+            let values = sequence.stack.values.take(n);
+
+            let values = quote_of(&state, values);
+            Ok(state.push_quote(values))
+        }),
+        RailDef::on_state("drop-while", "Consume one quote as a list and another quote as a predicate. Produce a quote without the leading values for which the predicate is true.", &[Quote, Quote], &[Quote], |state| {
+            let (predicate, state) = state.pop_quote("drop-while");
+            let (sequence, state) = state.pop_quote("drop-while");
+
+            let n = count_while(&state, &predicate, &sequence, "drop-while")?;
+            let values = sequence.stack.values.skip(n);
+
+            let values = quote_of(&state, values);
+            Ok(state.push_quote(values))
+        }),
+        RailDef::on_state("find", "Consume one quote as a list and another quote as a predicate. Produce the first value for which the predicate is true, or none if there is no such value.", &[Quote, Quote], &[A], |state| {
+            let (predicate, state) = state.pop_quote("find");
+            let (sequence, state) = state.pop_quote("find");
+
+            for term in sequence.stack.values {
+                if test_value(&state, &predicate, term.clone(), "find")? {
+                    return Ok(state.push(term));
+                }
+            }
+
+            Ok(state.push_none())
+        }),
+        RailDef::on_state_noerr("index-of", "Consume a quote and a value, and produce the 0-indexed location of the first equal value in the quote, or -1 if there is none.", &[Quote, A], &[I64], |state| {
+            let (a, state) = state.pop();
+            let (sequence, state) = state.pop_quote("index-of");
+
+            let index = sequence
+                .stack
+                .values
+                .index_of(&a)
+                .map(|i| i as i64)
+                .unwrap_or(-1);
+
+            state.push_i64(index)
+        }),
+        RailDef::on_state("all?", "Consume one quote as a list and another quote as a predicate. Produce true if the predicate is true for every value in the list, and false otherwise.", &[Quote, Quote], &[Boolean], |state| {
+            let (predicate, state) = state.pop_quote("all?");
+            let (sequence, state) = state.pop_quote("all?");
+
+            for term in sequence.stack.values {
+                if !test_value(&state, &predicate, term, "all?")? {
+                    return Ok(state.push_bool(false));
+                }
+            }
+
+            Ok(state.push_bool(true))
+        }),
+        RailDef::on_state("any?", ANY, &[Quote, Quote], &[Boolean], any("any?")),
+        RailDef::on_state("any", ANY, &[Quote, Quote], &[Boolean], any("any")),
+        RailDef::on_state("group-by", "Consume one quote as a list and another quote as a key function. Produce a symbol table of each key to a quote of the values that produced it. Keys that are not strings are converted to strings.", &[Quote, Quote], &[Stab], |state| {
+            let (key_fn, state) = state.pop_quote("group-by");
+            let (sequence, state) = state.pop_quote("group-by");
+
+            let mut groups = rail_machine::new_stab();
+
+            for term in sequence.stack.values {
+                let substate = state.child().replace_stack(Stack::of(term.clone()));
+                let substate = key_fn.clone().jailed_run_in_state(substate)?;
+                let (key, _) = substate.stack.pop();
+                let key = match key {
+                    RailVal::String(s) => s,
+                    key => key.to_string(),
+                };
+
+                let group = match groups.get(&key) {
+                    Some(RailVal::Quote(group)) => group.clone(),
+                    _ => state.child(),
+                };
+                groups.insert(key, RailVal::Quote(group.push(term)));
+            }
+
+            Ok(state.push_stab(groups))
+        }),
+        RailDef::on_state("partition", "Consume one quote as a list and another quote as a predicate. Produce a quote of the values for which the predicate is true, and a quote of the values for which it is false.", &[Quote, Quote], &[Quote, Quote], |state| {
+            let (predicate, state) = state.pop_quote("partition");
+            let (sequence, state) = state.pop_quote("partition");
+
+            let mut pass = state.child();
+            let mut fail = state.child();
+
+            for term in sequence.stack.values {
+                if test_value(&state, &predicate, term.clone(), "partition")? {
+                    pass = pass.push(term);
+                } else {
+                    fail = fail.push(term);
+                }
+            }
+
+            Ok(state.push_quote(pass).push_quote(fail))
+        }),
+        RailDef::on_state_noerr("chunks", "Consume a quote and a positive integer n, and produce a quote of consecutive quotes of n values. The last quote may be shorter.", &[Quote, I64], &[Quote], |state| {
+            let (n, state) = state.pop_i64("chunks");
+            let (sequence, state) = state.pop_quote("chunks");
+
+            if n < 1 {
+                log::warn(state.conventions, format!("Can only perform chunks with a positive size, but got {}", n));
+                return state.push_quote(sequence).push_i64(n);
+            }
+
+            let values = sequence.stack.values.into_iter().collect::<Vec<_>>();
+            let chunks = values
+                .chunks(n as usize)
+                .map(|chunk| quote_of(&state, chunk.iter().cloned().collect()))
+                .fold(state.child(), |chunks, chunk| chunks.push_quote(chunk));
+
+            state.push_quote(chunks)
+        }),
+        RailDef::on_state_noerr("windows", "Consume a quote and a positive integer n, and produce a quote of every run of n consecutive values.", &[Quote, I64], &[Quote], |state| {
+            let (n, state) = state.pop_i64("windows");
+            let (sequence, state) = state.pop_quote("windows");
+
+            if n < 1 {
+                log::warn(state.conventions, format!("Can only perform windows with a positive size, but got {}", n));
+                return state.push_quote(sequence).push_i64(n);
+            }
+
+            let values = sequence.stack.values.into_iter().collect::<Vec<_>>();
+            let windows = values
+                .windows(n as usize)
+                .map(|window| quote_of(&state, window.iter().cloned().collect()))
+                .fold(state.child(), |windows, window| windows.push_quote(window));
+
+            state.push_quote(windows)
+        }),
+        RailDef::on_state_noerr("flatten", "Consume a quote of quotes, and produce a quote of all their values. Values that are not quotes are kept as they are.", &[Quote], &[Quote], |state| {
+            let (sequence, state) = state.pop_quote("flatten");
+
+            let flat = sequence
+                .stack
+                .values
+                .into_iter()
+                .flat_map(|value| match value {
+                    RailVal::Quote(quote) => quote.stack.values,
+                    value => Vector::unit(value),
+                })
+                .fold(state.child(), |flat, value| flat.push(value));
+
+            state.push_quote(flat)
+        }),
+        RailDef::on_state_noerr("range", "Consume an integer as a start, an integer as an end, and an integer as a step. Produce a quote of integers from the start up to and including the end, separated by the step. The start is always included, even when it is already past the end.", &[I64, I64, I64], &[Quote], |state| {
+            let (step, state) = state.pop_i64("range");
+            let (end, state) = state.pop_i64("range");
+            let (start, state) = state.pop_i64("range");
+
+            if step == 0 {
+                log::warn(state.conventions, "Can only perform range with a non-zero step");
+                return state.push_i64(start).push_i64(end).push_i64(step);
+            }
+
+            let mut range = state.child().push_i64(start);
+            let mut n = start;
+
+            while let Some(next) = n.checked_add(step) {
+                if (step > 0 && next > end) || (step < 0 && next < end) {
+                    break;
+                }
+                range = range.push_i64(next);
+                n = next;
+            }
+
+            state.push_quote(range)
+        }),
     ]
 }
+
+fn any(name: &'static str) -> impl Fn(RailState) -> RailRunResult {
+    move |state| {
+        let (predicate, state) = state.pop_quote(name);
+        let (sequence, state) = state.pop_quote(name);
+
+        for term in sequence.stack.values {
+            if test_value(&state, &predicate, term, name)? {
+                return Ok(state.push_bool(true));
+            }
+        }
+
+        Ok(state.push_bool(false))
+    }
+}
+
+fn get_nth(sequence: &RailState, nth: i64) -> Option<RailVal> {
+    usize::try_from(nth)
+        .ok()
+        .and_then(|nth| sequence.stack.values.get(nth))
+        .cloned()
+}
+
+fn quote_of(state: &RailState, values: Vector<RailVal>) -> RailState {
+    state.child().replace_stack(Stack::new(values))
+}
+
+fn clamp_index(n: i64, len: usize) -> usize {
+    n.clamp(0, len as i64) as usize
+}
+
+fn test_value(
+    state: &RailState,
+    predicate: &RailState,
+    term: RailVal,
+    context: &str,
+) -> Result<bool, RailFailure> {
+    let substate = state.child().replace_stack(Stack::of(term));
+    let substate = predicate.clone().jailed_run_in_state(substate)?;
+    let (pass, _) = substate.stack.pop_bool(context);
+    Ok(pass)
+}
+
+fn count_while(
+    state: &RailState,
+    predicate: &RailState,
+    sequence: &RailState,
+    context: &str,
+) -> Result<usize, RailFailure> {
+    let mut n = 0;
+    for term in sequence.stack.values.iter() {
+        if !test_value(state, predicate, term.clone(), context)? {
+            break;
+        }
+        n += 1;
+    }
+    Ok(n)
+}
+
+fn combine_values(
+    state: &RailState,
+    combine: &RailState,
+    acc: RailVal,
+    term: RailVal,
+) -> Result<RailVal, RailFailure> {
+    let substate = state.child().push(acc).push(term);
+    let substate = combine.clone().jailed_run_in_state(substate)?;
+    let (acc, _) = substate.pop();
+    Ok(acc)
+}
@@ -0,0 +1,57 @@
+use crate::v1::rail_machine::{self, RailDef, RailType};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state_noerr("set", "Consume a quote, and produce a set of its values. Duplicate values are only kept once.", &[Quote], &[Set], |quote| {
+            let (values, quote) = quote.pop_quote("set");
+            let set = values
+                .stack
+                .values
+                .into_iter()
+                .fold(rail_machine::new_set(), |mut set, value| {
+                    set.insert(rail_machine::set_key(&value), value);
+                    set
+                });
+            quote.push_set(set)
+        }),
+        RailDef::on_state_noerr("members", "Consume a set, and produce a quote of its values in a consistent order.", &[Set], &[Quote], |quote| {
+            let (set, quote) = quote.pop_set("members");
+            let members = set
+                .values()
+                .fold(quote.child(), |members, value| members.push(value.clone()));
+            quote.push_quote(members)
+        }),
+        RailDef::on_state_noerr("add", "Consume a set and a value. Produce an identical set with the value added.", &[Set, A], &[Set], |quote| {
+            let (value, quote) = quote.pop();
+            let (mut set, quote) = quote.pop_set("add");
+            set.insert(rail_machine::set_key(&value), value);
+            quote.push_set(set)
+        }),
+        RailDef::on_state_noerr("member?", "Consume a set and a value. Produce true if the value is in the set, and false otherwise.", &[Set, A], &[Boolean], |quote| {
+            let (value, quote) = quote.pop();
+            let (set, quote) = quote.pop_set("member?");
+            quote.push_bool(set.contains_key(&rail_machine::set_key(&value)))
+        }),
+        set_op("union", "Consume two sets, and produce a set with the values of both.", |a, b| a.union(b)),
+        set_op("intersection", "Consume two sets, and produce a set with only the values they share.", |a, b| a.intersection(b)),
+        set_op("difference", "Consume two sets, and produce a set with the values of the first that are not in the second.", |a, b| a.relative_complement(b)),
+        RailDef::on_state_noerr("subset?", "Consume two sets. Produce true if every value of the first is also in the second, and false otherwise.", &[Set, Set], &[Boolean], |quote| {
+            let (b, quote) = quote.pop_set("subset?");
+            let (a, quote) = quote.pop_set("subset?");
+            quote.push_bool(a.keys().all(|k| b.contains_key(k)))
+        }),
+    ]
+}
+
+fn set_op<'a, F>(name: &'a str, description: &'a str, op: F) -> RailDef<'a>
+where
+    F: Fn(rail_machine::Set, rail_machine::Set) -> rail_machine::Set + Sized + 'a,
+{
+    RailDef::on_state_noerr(name, description, &[Set, Set], &[Set], move |quote| {
+        let (b, quote) = quote.pop_set(name);
+        let (a, quote) = quote.pop_set(name);
+        quote.push_set(op(a, b))
+    })
+}
@@ -0,0 +1,41 @@
+use crate::parser::{self, Node};
+use crate::tokens::Span;
+use crate::v1::rail_machine::{RailDef, RailError, RailRunResult, RailState, RailType};
+
+use RailType::*;
+
+pub fn builtins() -> Vec<RailDef<'static>> {
+    vec![
+        RailDef::on_state_noerr("to-source", "Consume a value, and produce a string of Rail source that evaluates to an equal value.", &[A], &[String], |state| {
+            let (value, state) = state.pop();
+            state.push_string(value.to_source())
+        }),
+        RailDef::on_state("parse", "Consume a string of Rail source, and produce it as a quote without running it.", &[String], &[Quote], |state| {
+            let (source, state) = state.pop_string("parse");
+            match parse(&state, &source) {
+                Ok(quote) => Ok(state.push_quote(quote)),
+                Err(failure) => Err(Box::new((state.push_string(source), failure.1))),
+            }
+        }),
+        RailDef::on_state("eval", "Consume a string of Rail source, and run it. Any new definitions are preserved in the calling context.", &[String], &[Unknown], |state| {
+            let (source, state) = state.pop_string("eval");
+            match parse(&state, &source) {
+                Ok(quote) => quote.run_in_state(state),
+                Err(failure) => Err(Box::new((state.push_string(source), failure.1))),
+            }
+        }),
+    ]
+}
+
+/// Read source into a quote, without running any of it.
+fn parse(state: &RailState, source: &str) -> RailRunResult {
+    let nodes = match parser::parse(source) {
+        Ok(nodes) => nodes,
+        Err(e) => return Err(Box::new((state.clone(), RailError::Parse(e)))),
+    };
+
+    let start = Span { line: 1, column: 1 };
+    let parsed = state.child().run_node(Node::Quote(nodes, start))?;
+    let (quote, _) = parsed.pop_quote("parse");
+    Ok(quote)
+}
@@ -1,4 +1,7 @@
-use crate::v1::rail_machine::{self, RailDef, RailType};
+use crate::v1::log;
+use crate::v1::rail_machine::{
+    self, RailDef, RailError, RailFailure, RailState, RailType, RailVal, Stab, Stack,
+};
 
 use RailType::*;
 
@@ -13,11 +16,210 @@ pub fn builtins() -> Vec<RailDef<'static>> {
             st.insert(k, v);
             quote.push_stab(st)
         }),
-        RailDef::on_state_noerr("extract", "Consume a symbol table and a string as a key, produce an identical symbol table and the value relating to the key.", &[Stab, String], &[Stab, A], |quote| {
-            let (k, quote) = quote.pop_string("insert");
-            let (st, quote) = quote.pop_stab("insert");
-            let result = st.get(&k).unwrap().to_owned();
-            quote.push_stab(st).push(result)
+        RailDef::on_state("extract", "Consume a symbol table and a string as a key, produce an identical symbol table and the value relating to the key.", &[Stab, String], &[Stab, A], |quote| {
+            let (k, quote) = quote.pop_string("extract");
+            let (st, quote) = quote.pop_stab("extract");
+            match st.get(&k).cloned() {
+                Some(result) => Ok(quote.push_stab(st).push(result)),
+                None => Err(Box::new((quote.push_stab(st).push_string(k.clone()), RailError::MissingKey("extract".into(), k)))),
+            }
+        }),
+        RailDef::on_state_noerr("extract?", "Consume a symbol table and a string as a key, produce an identical symbol table and the value relating to the key, or none if the key is absent.", &[Stab, String], &[Stab, A], |quote| {
+            let (k, quote) = quote.pop_string("extract?");
+            let (st, quote) = quote.pop_stab("extract?");
+            let found = st.get(&k).cloned();
+            quote.push_stab(st).push_optional(found)
+        }),
+        RailDef::on_state_noerr("has-key?", "Consume a symbol table and a string as a key. Produce true if the key is in the symbol table, and false otherwise.", &[Stab, String], &[Boolean], |quote| {
+            let (k, quote) = quote.pop_string("has-key?");
+            let (st, quote) = quote.pop_stab("has-key?");
+            quote.push_bool(st.contains_key(&k))
+        }),
+        RailDef::on_state_noerr("remove", "Consume a symbol table and a string as a key, or a set and a value. Produce an identical symbol table without the key, or an identical set without the value.", &[A, A], &[A], |quote| {
+            let (v, quote) = quote.pop();
+            let (container, quote) = quote.pop();
+            match (container, v) {
+                (RailVal::Stab(st), RailVal::String(k)) => quote.push_stab(st.without(&k)),
+                (RailVal::Set(set), v) => quote.push_set(set.without(&rail_machine::set_key(&v))),
+                (container, v) => {
+                    log::warn(
+                        quote.conventions,
+                        format!("Can only remove a string key from a symbol table or a value from a set, but got {} and {}", container, v),
+                    );
+                    quote.push(container).push(v)
+                }
+            }
+        }),
+        RailDef::on_state_noerr("keys", "Consume a symbol table, and produce a list of its keys in sorted order.", &[Stab], &[Quote], |quote| {
+            let (st, quote) = quote.pop_stab("keys");
+            let keys = rail_machine::sorted_entries(&st)
+                .into_iter()
+                .fold(quote.child(), |keys, (k, _)| keys.push_str(k));
+            quote.push_quote(keys)
+        }),
+        RailDef::on_state_noerr("values", "Consume a symbol table, and produce a list of its values, ordered by their keys.", &[Stab], &[Quote], |quote| {
+            let (st, quote) = quote.pop_stab("values");
+            let values = rail_machine::sorted_entries(&st)
+                .into_iter()
+                .fold(quote.child(), |values, (_, v)| values.push(v.clone()));
+            quote.push_quote(values)
+        }),
+        RailDef::on_state_noerr("entries", "Consume a symbol table, and produce a list of its key + value pairs, ordered by their keys.", &[Stab], &[Quote], |quote| {
+            let (st, quote) = quote.pop_stab("entries");
+            let entries = entries_of(&quote, &st);
+            quote.push_quote(entries)
+        }),
+        RailDef::on_state_noerr("from-entries", "Consume a list of key + value pairs, and produce a symbol table containing them. When a key appears more than once, the last value is kept, as with insert and merge.", &[Quote], &[Stab], |quote| {
+            let (entries, quote) = quote.pop_quote("from-entries");
+            // Reversed so entries are popped in order, and later ones overwrite earlier ones.
+            let mut entries = entries.stack.reverse();
+            let mut st = rail_machine::new_stab();
+            while !entries.is_empty() {
+                let (k, v, rest) = entries.pop_stab_entry("from-entries");
+                st.insert(k, v);
+                entries = rest;
+            }
+            quote.push_stab(st)
+        }),
+        RailDef::on_state_noerr("merge", "Consume two symbol tables, and produce a symbol table with the entries of both. When both have a key, the value from the second is kept.", &[Stab, Stab], &[Stab], |quote| {
+            let (overrides, quote) = quote.pop_stab("merge");
+            let (mut st, quote) = quote.pop_stab("merge");
+            st.extend(overrides);
+            quote.push_stab(st)
+        }),
+        RailDef::on_state("update", "Consume a symbol table, a string as a key, and a quote. Produce an identical symbol table where the key's value has been transformed by the quote.", &[Stab, String, Quote], &[Stab], |quote| {
+            let (transform, quote) = quote.pop_quote("update");
+            let (k, quote) = quote.pop_string("update");
+            let (mut st, quote) = quote.pop_stab("update");
+
+            let v = match st.get(&k) {
+                Some(v) => v.clone(),
+                None => {
+                    return Err(Box::new((
+                        quote.push_stab(st).push_string(k.clone()).push_quote(transform),
+                        RailError::MissingKey("update".into(), k),
+                    )))
+                }
+            };
+
+            let v = transform_value(&quote, &transform, v)?;
+            st.insert(k, v);
+            Ok(quote.push_stab(st))
+        }),
+        RailDef::on_state("stab-map", "Consume a symbol table and a quote. Produce a symbol table with the same keys, where every value has been transformed by the quote.", &[Stab, Quote], &[Stab], |quote| {
+            let (transform, quote) = quote.pop_quote("stab-map");
+            let (st, quote) = quote.pop_stab("stab-map");
+
+            let mut results = rail_machine::new_stab();
+            for (k, v) in st {
+                let v = transform_value(&quote, &transform, v)?;
+                results.insert(k, v);
+            }
+
+            Ok(quote.push_stab(results))
+        }),
+        RailDef::on_state("stab-filter", "Consume a symbol table and a quote as a predicate. The predicate is given each key and value, and a symbol table is produced with only the entries for which it is true.", &[Stab, Quote], &[Stab], |quote| {
+            let (predicate, quote) = quote.pop_quote("stab-filter");
+            let (st, quote) = quote.pop_stab("stab-filter");
+
+            let mut results = rail_machine::new_stab();
+            for (k, v) in st {
+                let substate = quote.child().push_str(&k).push(v.clone());
+                let substate = predicate.clone().jailed_run_in_state(substate)?;
+                let (keep, _) = substate.stack.pop_bool("stab-filter");
+                if keep {
+                    results.insert(k, v);
+                }
+            }
+
+            Ok(quote.push_stab(results))
+        }),
+        RailDef::on_state("get-in", "Consume a symbol table and a list of keys as a path through nested symbol tables. Produce the value at the end of the path, or none if any key along it is absent.", &[Stab, Quote], &[A], |quote| {
+            let (path, quote) = quote.pop_quote("get-in");
+            let (st, quote) = quote.pop_stab("get-in");
+
+            let keys = path_keys(&quote.clone().push_stab(st.clone()), &path)?;
+
+            let mut value = RailVal::Stab(st);
+            for k in keys {
+                value = match value {
+                    RailVal::Stab(st) => st.get(&k).cloned().unwrap_or(RailVal::None),
+                    _ => RailVal::None,
+                };
+            }
+
+            Ok(quote.push(value))
+        }),
+        RailDef::on_state("set-in", "Consume a symbol table, a list of keys as a path through nested symbol tables, and a value. Produce an identical symbol table with the value at the end of the path. Missing symbol tables along the path are created, but any other value in the way is an error, as is an empty path.", &[Stab, Quote, A], &[Stab], |quote| {
+            let (value, quote) = quote.pop();
+            let (path, quote) = quote.pop_quote("set-in");
+            let (st, quote) = quote.pop_stab("set-in");
+
+            let restore = quote.clone().push_stab(st.clone());
+            let keys = path_keys(&restore, &path).map_err(|failure| {
+                let (state, e) = *failure;
+                Box::new((state.push(value.clone()), e))
+            })?;
+
+            match set_in(st, &keys, value.clone()) {
+                Ok(st) => Ok(quote.push_stab(st)),
+                Err(e) => Err(Box::new((restore.push_quote(path).push(value), e))),
+            }
         }),
     ]
 }
+
+fn entries_of(state: &RailState, st: &Stab) -> RailState {
+    rail_machine::sorted_entries(st)
+        .into_iter()
+        .fold(state.child(), |entries, (k, v)| {
+            let entry = state.child().push_str(k).push(v.clone());
+            entries.push_quote(entry)
+        })
+}
+
+fn transform_value(
+    state: &RailState,
+    transform: &RailState,
+    value: RailVal,
+) -> Result<RailVal, RailFailure> {
+    let substate = state.child().replace_stack(Stack::of(value));
+    let substate = transform.clone().jailed_run_in_state(substate)?;
+    let (value, _) = substate.pop();
+    Ok(value)
+}
+
+fn path_keys(state: &RailState, path: &RailState) -> Result<Vec<std::string::String>, RailFailure> {
+    path.stack
+        .values
+        .iter()
+        .map(|k| match k {
+            RailVal::String(k) => Ok(k.clone()),
+            k => Err(Box::new((
+                state.clone().push_quote(path.clone()),
+                RailError::TypeMismatch(vec![String], vec![k.clone()]),
+            ))),
+        })
+        .collect()
+}
+
+fn set_in(mut st: Stab, keys: &[std::string::String], value: RailVal) -> Result<Stab, RailError> {
+    match keys {
+        [] => Err(RailError::EmptyCollection("set-in".into())),
+        [k] => {
+            st.insert(k.clone(), value);
+            Ok(st)
+        }
+        [k, rest @ ..] => {
+            let inner = match st.get(k) {
+                Some(RailVal::Stab(inner)) => inner.clone(),
+                Some(other) => {
+                    return Err(RailError::TypeMismatch(vec![Stab], vec![other.clone()]))
+                }
+                None => rail_machine::new_stab(),
+            };
+            st.insert(k.clone(), RailVal::Stab(set_in(inner, rest, value)?));
+            Ok(st)
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use crate::v1::rail_machine::{Dictionary, RailDef};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+/// A reference of every command in the dictionary, grouped by where each was defined.
+pub fn reference(definitions: &Dictionary, format: DocFormat) -> String {
+    let modules = by_origin(definitions);
+    match format {
+        DocFormat::Markdown => markdown(&modules),
+        DocFormat::Html => html(&modules),
+    }
+}
+
+/// Commands whose name or description contains the search term, ignoring case.
+pub fn apropos(definitions: &Dictionary, term: &str) -> Vec<String> {
+    let term = term.to_lowercase();
+    let mut names = definitions
+        .keys()
+        .filter(|name| {
+            let description = definitions
+                .get(name)
+                .map(|def| def.description.to_lowercase());
+            name.to_lowercase().contains(&term) || description.is_some_and(|d| d.contains(&term))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn by_origin(definitions: &Dictionary) -> BTreeMap<String, Vec<&RailDef<'static>>> {
+    let mut modules: BTreeMap<String, Vec<&RailDef>> = BTreeMap::new();
+
+    for name in definitions.sorted_keys() {
+        let def = definitions.get(name).unwrap();
+        let origin = if def.origin.is_empty() {
+            "other".to_string()
+        } else {
+            def.module().to_string()
+        };
+        modules.entry(origin).or_default().push(def);
+    }
+
+    modules
+}
+
+fn description(def: &RailDef) -> String {
+    def.description
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn markdown(modules: &BTreeMap<String, Vec<&RailDef>>) -> String {
+    let mut doc = String::from("# Rail reference\n");
+
+    for (origin, defs) in modules {
+        doc.push_str(&format!("\n## {}\n", origin));
+
+        for def in defs {
+            doc.push_str(&format!("\n### `{}`\n\n", def.name));
+            if let Some(signature) = def.signature() {
+                doc.push_str(&format!("`{}`\n\n", signature));
+            }
+            doc.push_str(&format!("{}\n", description(def)));
+        }
+    }
+
+    doc
+}
+
+fn html(modules: &BTreeMap<String, Vec<&RailDef>>) -> String {
+    let mut doc = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Rail reference</title>\n</head>\n<body>\n<h1>Rail reference</h1>\n<ul>\n",
+    );
+
+    for origin in modules.keys() {
+        doc.push_str(&format!(
+            "<li><a href=\"#{0}\">{0}</a></li>\n",
+            escape(origin)
+        ));
+    }
+    doc.push_str("</ul>\n");
+
+    for (origin, defs) in modules {
+        doc.push_str(&format!("<h2 id=\"{0}\">{0}</h2>\n<dl>\n", escape(origin)));
+
+        for def in defs {
+            doc.push_str(&format!("<dt><code>{}</code>", escape(&def.name)));
+            if let Some(signature) = def.signature() {
+                doc.push_str(&format!(" <code>{}</code>", escape(&signature)));
+            }
+            doc.push_str(&format!("</dt>\n<dd>{}</dd>\n", escape(&description(def))));
+        }
+
+        doc.push_str("</dl>\n");
+    }
+
+    doc.push_str("</body>\n</html>\n");
+    doc
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
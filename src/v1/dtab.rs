@@ -0,0 +1,83 @@
+use im::{HashMap, Vector};
+
+/// A definition table: a map of key to a stack of values, loosely inspired by
+/// Forth's dictionary. Setting a key shadows its previous value, and unsetting
+/// it brings the previous value back.
+#[derive(Clone, PartialEq)]
+pub struct Dtab<T: Clone> {
+    entries: HashMap<String, Vector<T>>,
+}
+
+impl<T: Clone> Dtab<T> {
+    pub fn new() -> Self {
+        Dtab {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Push a value for the key, shadowing any value it already had.
+    pub fn insert(&mut self, key: String, value: T) {
+        self.entries.entry(key).or_default().push_back(value);
+    }
+
+    /// Replace the latest value for the key, or set it if it had none. Use this
+    /// for rebinding that shouldn't build up history, like local variables.
+    pub fn replace(&mut self, key: String, value: T) {
+        let shadows = self.entries.entry(key).or_default();
+        shadows.pop_back();
+        shadows.push_back(value);
+    }
+
+    /// The latest value for the key.
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.entries.get(key).and_then(|shadows| shadows.last())
+    }
+
+    /// Remove and return the latest value for the key, restoring whatever it shadowed.
+    pub fn unset(&mut self, key: &str) -> Option<T> {
+        let shadows = self.entries.get_mut(key)?;
+        let latest = shadows.pop_back();
+        if shadows.is_empty() {
+            self.entries.remove(key);
+        }
+        latest
+    }
+
+    /// Every value the key has had, newest first.
+    pub fn history(&self, key: &str) -> Vec<&T> {
+        self.entries
+            .get(key)
+            .map(|shadows| shadows.iter().rev().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.keys()
+    }
+
+    /// The keys in sorted order, for deterministic output.
+    pub fn sorted_keys(&self) -> Vec<&String> {
+        let mut keys = self.keys().collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+}
+
+impl<T: Clone> Default for Dtab<T> {
+    fn default() -> Self {
+        Dtab::new()
+    }
+}
+
+impl<T: Clone> FromIterator<(String, T)> for Dtab<T> {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> Self {
+        iter.into_iter().fold(Dtab::new(), |mut dtab, (k, v)| {
+            dtab.insert(k, v);
+            dtab
+        })
+    }
+}
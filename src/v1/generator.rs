@@ -0,0 +1,263 @@
+use im::Vector;
+use std::cell::RefCell;
+use std::io::BufRead;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::v1::rail_machine::{RailError, RailRunResult, RailState, RailVal, Stack};
+
+/// A lazy sequence of values, made a few at a time as they're asked for.
+///
+/// Generators are values like any other: taking the next value produces a new
+/// generator for the rest, and leaves the old one as it was. The exception is
+/// generators of lines being read, which every copy reads from together.
+#[derive(Clone)]
+pub struct Generator {
+    /// Shared by copies of the generator, and new for each generator made.
+    id: u64,
+    source: Box<Source>,
+    /// Values already made but not yet taken.
+    buffer: Vector<RailVal>,
+}
+
+#[derive(Clone)]
+enum Source {
+    /// A stack and a step that runs on it, yielding values and leaving the
+    /// stack for the next step. A step that yields nothing ends the generator.
+    Step {
+        stack: RailState,
+        step: RailState,
+    },
+    /// Another generator's values, each run through an action.
+    Map(Box<Generator>, RailState),
+    /// Another generator's values that the predicate is true for.
+    Filter(Box<Generator>, RailState),
+    /// At most this many of another generator's values.
+    Take(Box<Generator>, usize),
+    /// Lines read from a file as they're asked for.
+    Lines(String, Rc<RefCell<Box<dyn BufRead>>>),
+    /// Lines read from standard input as they're asked for.
+    Stdin,
+    Done,
+}
+
+impl Generator {
+    pub fn new(stack: RailState, step: RailState) -> Self {
+        Generator::of(Source::Step { stack, step })
+    }
+
+    /// A generator of a file's lines, read from the reader as they're asked for.
+    pub fn lines(path: String, reader: Box<dyn BufRead>) -> Self {
+        Generator::of(Source::Lines(path, Rc::new(RefCell::new(reader))))
+    }
+
+    pub fn stdin_lines() -> Self {
+        Generator::of(Source::Stdin)
+    }
+
+    fn done() -> Self {
+        Generator::of(Source::Done)
+    }
+
+    fn of(source: Source) -> Self {
+        Generator::buffered(source, Vector::new())
+    }
+
+    fn buffered(source: Source, buffer: Vector<RailVal>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Generator {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            source: Box::new(source),
+            buffer,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// A generator of the values produced by running the action on each value
+    /// of this one. Nothing runs until its values are asked for.
+    pub fn map(self, action: RailState) -> Self {
+        Generator::of(Source::Map(Box::new(self), action))
+    }
+
+    /// A generator of the values of this one that the predicate is true for.
+    pub fn filter(self, predicate: RailState) -> Self {
+        Generator::of(Source::Filter(Box::new(self), predicate))
+    }
+
+    /// A generator of at most the first n values of this one.
+    pub fn take(self, n: usize) -> Self {
+        Generator::of(Source::Take(Box::new(self), n))
+    }
+
+    /// The next value, if there is one, and the generator for the rest.
+    pub fn next(mut self, state: &RailState) -> Result<(Option<RailVal>, Generator), RailError> {
+        if let Some(value) = self.buffer.pop_front() {
+            return Ok((Some(value), Generator::buffered(*self.source, self.buffer)));
+        }
+
+        match *self.source {
+            Source::Step { stack, step } => {
+                let yielded = Rc::new(RefCell::new(Vector::new()));
+                let mut substate = state.child().replace_stack(stack.stack.clone());
+                substate.yielded = Some(yielded.clone());
+
+                let after = step
+                    .clone()
+                    .jailed_run_in_state(substate)
+                    .map_err(|failure| failure.1)?;
+
+                let buffer = yielded.take();
+                if buffer.is_empty() {
+                    return Ok((None, Generator::done()));
+                }
+
+                let stack = stack.replace_stack(after.stack);
+                Generator::buffered(Source::Step { stack, step }, buffer).next(state)
+            }
+            Source::Map(inner, action) => {
+                let mut inner = *inner;
+                loop {
+                    let (value, rest) = inner.next(state)?;
+                    let value = match value {
+                        Some(value) => value,
+                        None => return Ok((None, Generator::done())),
+                    };
+
+                    let results = action
+                        .clone()
+                        .jailed_run_in_state(action_state(state).push(value))
+                        .map_err(|failure| failure.1)?;
+
+                    inner = rest;
+                    if !results.is_empty() {
+                        let source = Source::Map(Box::new(inner), action);
+                        return Generator::buffered(source, results.stack.values).next(state);
+                    }
+                }
+            }
+            Source::Filter(inner, predicate) => {
+                let mut inner = *inner;
+                loop {
+                    let (value, rest) = inner.next(state)?;
+                    let value = match value {
+                        Some(value) => value,
+                        None => return Ok((None, Generator::done())),
+                    };
+
+                    let substate = action_state(state).replace_stack(Stack::of(value.clone()));
+                    let substate = predicate
+                        .clone()
+                        .jailed_run_in_state(substate)
+                        .map_err(|failure| failure.1)?;
+                    let (keep, _) = substate.stack.pop_bool("filter");
+
+                    inner = rest;
+                    if keep {
+                        let rest = Generator::of(Source::Filter(Box::new(inner), predicate));
+                        return Ok((Some(value), rest));
+                    }
+                }
+            }
+            Source::Take(_, 0) => Ok((None, Generator::done())),
+            Source::Take(inner, n) => match inner.next(state)? {
+                (Some(value), rest) => {
+                    let rest = Generator::of(Source::Take(Box::new(rest), n - 1));
+                    Ok((Some(value), rest))
+                }
+                (None, _) => Ok((None, Generator::done())),
+            },
+            Source::Lines(path, reader) => {
+                let line = read_line(|buf| reader.borrow_mut().read_line(buf));
+                let rest = match line {
+                    Some(_) => Generator::of(Source::Lines(path, reader)),
+                    None => Generator::done(),
+                };
+                Ok((line, rest))
+            }
+            Source::Stdin => {
+                let line = read_line(|buf| std::io::stdin().read_line(buf));
+                let rest = match line {
+                    Some(_) => Generator::stdin_lines(),
+                    None => Generator::done(),
+                };
+                Ok((line, rest))
+            }
+            Source::Done => Ok((None, Generator::done())),
+        }
+    }
+
+    /// Run an action on each value in turn, until the generator ends.
+    pub fn try_fold(
+        self,
+        state: RailState,
+        action: impl Fn(RailState, RailVal) -> RailRunResult,
+    ) -> RailRunResult {
+        let mut generator = self;
+        let mut state = state;
+        loop {
+            match generator.next(&state) {
+                Ok((Some(value), rest)) => {
+                    state = action(state, value)?;
+                    generator = rest;
+                }
+                Ok((None, _)) => return Ok(state),
+                Err(e) => return Err(Box::new((state, e))),
+            }
+        }
+    }
+
+    /// Rail source that makes a generator like this one. Values already made
+    /// but not yet taken are left out, and lines are read again from the start.
+    pub fn to_source(&self) -> String {
+        match self.source.as_ref() {
+            Source::Step { stack, step } => format!(
+                "{} {} generator",
+                RailVal::Quote(stack.clone()).to_source(),
+                RailVal::Quote(step.clone()).to_source()
+            ),
+            Source::Map(inner, action) => format!(
+                "{} {} map",
+                inner.to_source(),
+                RailVal::Quote(action.clone()).to_source()
+            ),
+            Source::Filter(inner, predicate) => format!(
+                "{} {} filter",
+                inner.to_source(),
+                RailVal::Quote(predicate.clone()).to_source()
+            ),
+            Source::Take(inner, n) => format!("{} {} take", inner.to_source(), n),
+            Source::Lines(path, _) => format!("{} lines-of", RailVal::String(path.clone())),
+            Source::Stdin => "stdin-lines".into(),
+            Source::Done => "[ ] [ ] generator".into(),
+        }
+    }
+}
+
+/// A state to run a map or filter action in. Its yields don't belong to any
+/// generator whose step is running, since the action isn't part of the step.
+fn action_state(state: &RailState) -> RailState {
+    let mut substate = state.child();
+    substate.yielded = None;
+    substate
+}
+
+/// The next line from a reader, without its line ending. Reading stops at the
+/// end of input, or at the first line that can't be read.
+fn read_line(read: impl FnOnce(&mut String) -> std::io::Result<usize>) -> Option<RailVal> {
+    let mut line = String::new();
+    match read(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => {
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            Some(RailVal::String(line))
+        }
+    }
+}
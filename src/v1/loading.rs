@@ -1,21 +1,32 @@
 use std::{fmt::Debug, fs, path::Path};
 
-use crate::tokens::{self, Token};
+use crate::forth;
+use crate::parser::{self, Node, ParseError};
 use crate::v1::corelib::rail_builtin_dictionary;
 use crate::v1::log;
 use crate::v1::rail_lib_path;
-use crate::v1::rail_machine::{RailRunResult, RailState, RunConventions};
+use crate::v1::rail_machine::{RailError, RailRunResult, RailState, RunConventions};
 
 pub struct SourceConventions<'a> {
     pub lib_exts: &'a [&'a str],
     pub lib_list_exts: &'a [&'a str],
+    pub forth_exts: &'a [&'a str],
 }
 
 pub const RAIL_SOURCE_CONVENTIONS: SourceConventions = SourceConventions {
     lib_exts: &[".rail"],
     lib_list_exts: &[".txt"],
+    forth_exts: &[".fth"],
 };
 
+/// The language a source is written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    Rail,
+    /// Simple Forth, read by the front end in [crate::forth].
+    Forth,
+}
+
 impl SourceConventions<'_> {
     pub fn is_lib(&self, filename: &str) -> bool {
         self.has_ext(filename, self.lib_exts)
@@ -25,6 +36,18 @@ impl SourceConventions<'_> {
         self.has_ext(filename, self.lib_list_exts)
     }
 
+    pub fn is_forth(&self, filename: &str) -> bool {
+        self.has_ext(filename, self.forth_exts)
+    }
+
+    pub fn dialect_of(&self, filename: &str) -> Dialect {
+        if self.is_forth(filename) {
+            Dialect::Forth
+        } else {
+            Dialect::Rail
+        }
+    }
+
     fn has_ext(&self, filename: &str, exts: &[&str]) -> bool {
         exts.iter().any(|ext| filename.ends_with(ext))
     }
@@ -38,40 +61,70 @@ pub fn initial_rail_state(
     let definitions = rail_builtin_dictionary();
     let state = RailState::new_main(definitions, rc);
 
-    let state = if skip_stdlib {
-        Ok(state)
+    let stdlib = if skip_stdlib {
+        Ok(vec![])
     } else {
-        let tokens = from_rail_stdlib(rc);
-        state.run_tokens(tokens)
+        rail_stdlib_libs(rc)
+    };
+    let libs = stdlib.and_then(|mut libs| {
+        if let Some(lib_list) = lib_list {
+            libs.extend(libs_in_list(lib_list, &RAIL_SOURCE_CONVENTIONS)?);
+        }
+        Ok(libs)
+    });
+    let libs = match libs {
+        Ok(libs) => libs,
+        Err(e) => return Err(Box::new((state, e))),
     };
 
-    if let Some(lib_list) = lib_list {
-        let tokens = from_lib_list(lib_list, &RAIL_SOURCE_CONVENTIONS);
-        state.and_then(|state| state.run_tokens(tokens))
-    } else {
-        state
+    let state = libs.into_iter().try_fold(state, |state, (origin, nodes)| {
+        state.with_origin(Some(origin)).run_nodes(nodes)
+    })?;
+
+    Ok(state.with_origin(None))
+}
+
+pub fn parse_source(source: String) -> Result<Vec<Node>, ParseError> {
+    parse_dialect(source, Dialect::Rail)
+}
+
+pub fn parse_dialect(source: String, dialect: Dialect) -> Result<Vec<Node>, ParseError> {
+    match dialect {
+        Dialect::Rail => parser::parse(&source),
+        Dialect::Forth => forth::parse(&source),
     }
 }
 
-pub fn get_source_as_tokens(source: String) -> Vec<Token> {
-    source.split('\n').flat_map(tokens::tokenize).collect()
+/// Parse a file, in the dialect its extension names.
+pub fn parse_source_file<P>(path: P) -> Result<Vec<Node>, RailError>
+where
+    P: AsRef<Path> + Debug,
+{
+    let dialect = RAIL_SOURCE_CONVENTIONS.dialect_of(&path.as_ref().to_string_lossy());
+    parse_source_file_in(path, dialect)
 }
 
-pub fn get_source_file_as_tokens<P>(path: P) -> Vec<Token>
+pub fn parse_source_file_in<P>(path: P, dialect: Dialect) -> Result<Vec<Node>, RailError>
 where
     P: AsRef<Path> + Debug,
 {
-    let error_msg = format!("Error reading file {:?}", path);
-    let source = fs::read_to_string(path).expect(&error_msg);
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)
+        .map_err(|e| RailError::CantOpen(path.to_string_lossy().to_string(), e.to_string()))?;
+
+    parse_dialect(source, dialect).map_err(RailError::Parse)
+}
 
-    get_source_as_tokens(source)
+pub fn from_rail_stdlib(rc: &RunConventions) -> Result<Vec<Node>, RailError> {
+    let libs = rail_stdlib_libs(rc)?;
+    Ok(libs.into_iter().flat_map(|(_, nodes)| nodes).collect())
 }
 
-pub fn from_rail_stdlib(rc: &RunConventions) -> Vec<Token> {
+fn rail_stdlib_libs(rc: &RunConventions) -> Result<Vec<(String, Vec<Node>)>, RailError> {
     let path = rail_lib_path(rc).join("rail-src/stdlib/all.txt");
 
     if path.is_file() {
-        return from_lib_list(path, &RAIL_SOURCE_CONVENTIONS);
+        return libs_in_list(path, &RAIL_SOURCE_CONVENTIONS);
     }
 
     let message = format!(
@@ -80,10 +133,24 @@ pub fn from_rail_stdlib(rc: &RunConventions) -> Vec<Token> {
     );
     log::warn(rc, message);
 
-    vec![]
+    Ok(vec![])
+}
+
+pub fn from_lib_list<P>(path: P, conventions: &SourceConventions) -> Result<Vec<Node>, RailError>
+where
+    P: AsRef<Path> + Debug,
+{
+    let libs = libs_in_list(path, conventions)?;
+    Ok(libs.into_iter().flat_map(|(_, nodes)| nodes).collect())
 }
 
-pub fn from_lib_list<P>(path: P, conventions: &SourceConventions) -> Vec<Token>
+/// Each library named by a library list, in order, as its origin (like
+/// `stdlib/math.rail`) and its parsed nodes. Fails on the first library or
+/// list that can't be read or parsed.
+pub fn libs_in_list<P>(
+    path: P,
+    conventions: &SourceConventions,
+) -> Result<Vec<(String, Vec<Node>)>, RailError>
 where
     P: AsRef<Path> + Debug,
 {
@@ -91,21 +158,33 @@ where
 
     let base_dir = path.parent().unwrap();
 
-    fs::read_to_string(path)
-        .unwrap_or_else(|_| panic!("Unable to load library list file {:?}", path))
+    let list = fs::read_to_string(path)
+        .map_err(|e| RailError::CantOpen(path.to_string_lossy().to_string(), e.to_string()))?;
+
+    let mut libs = vec![];
+    for filepath in list
         .split('\n')
         .filter(|s| !s.is_empty() && !s.starts_with('#'))
-        .map(|filepath| base_dir.join(filepath).to_string_lossy().to_string())
-        .map(|file| {
-            if conventions.is_lib(&file) {
-                Some(get_source_file_as_tokens(file))
-            } else if conventions.is_lib_list(&file) {
-                Some(from_lib_list(file, conventions))
-            } else {
-                None
-            }
-        })
-        .filter(|list| list.is_some())
-        .flat_map(|list| list.unwrap())
-        .collect::<Vec<_>>()
+    {
+        let file = base_dir.join(filepath);
+        let name = file.to_string_lossy().to_string();
+        if conventions.is_lib(&name) || conventions.is_forth(&name) {
+            let dialect = conventions.dialect_of(&name);
+            let nodes = parse_source_file_in(&file, dialect)?;
+            libs.push((lib_origin(&file), nodes));
+        } else if conventions.is_lib_list(&name) {
+            libs.extend(libs_in_list(file, conventions)?);
+        }
+    }
+
+    Ok(libs)
+}
+
+fn lib_origin(file: &Path) -> String {
+    let parent = file.parent().and_then(|dir| dir.file_name());
+    let name = file.file_name().unwrap_or_default();
+    match parent {
+        Some(parent) => format!("{}/{}", parent.to_string_lossy(), name.to_string_lossy()),
+        None => name.to_string_lossy().to_string(),
+    }
 }
@@ -19,7 +19,8 @@ pub fn warn(conv: &RunConventions, thing: impl Display) {
 pub fn warn_coerce(result: RailRunResult) -> RailState {
     match result {
         Ok(state) => state,
-        Err((state, err)) => {
+        Err(failure) => {
+            let (state, err) = *failure;
             warn(state.conventions, format!("{:?}", err));
             state
         }
@@ -34,7 +35,8 @@ pub fn error(conv: &RunConventions, thing: impl Display) {
 pub fn error_coerce(result: RailRunResult) -> RailState {
     match result {
         Ok(state) => state,
-        Err((state, err)) => {
+        Err(failure) => {
+            let (state, err) = *failure;
             error(state.conventions, format!("{:?}", err));
             state
         }
@@ -5,8 +5,12 @@ pub use rail_machine::RunConventions;
 use std::path::PathBuf;
 
 pub mod corelib;
+pub mod docs;
+pub mod dtab;
+pub mod generator;
 pub mod loading;
 pub mod log;
+pub mod pattern;
 pub mod prompt;
 pub mod rail_machine;
 
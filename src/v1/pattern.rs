@@ -0,0 +1,96 @@
+use crate::v1::rail_machine::{RailDef, RailState, RailVal};
+
+/// Names bound by a successful match, in the order they appeared in the pattern.
+pub type Bindings = Vec<(String, RailVal)>;
+
+/// Match a value against a pattern, collecting any bindings.
+///
+/// * A command binds the value to its name, except `_` which matches anything.
+/// * A quote takes apart the value depending on its type: a quote of the same
+///   length position by position, a symbol table by key + pattern pairs, or a
+///   record by its variant name followed by one pattern per field.
+/// * Anything else is a literal, and only matches an equal value.
+pub fn bind(pattern: &RailVal, value: &RailVal, bindings: &mut Bindings) -> bool {
+    match (pattern, value) {
+        (RailVal::Command(name) | RailVal::DeferredCommand(name), _) => {
+            if name != "_" {
+                bindings.push((name.clone(), value.clone()));
+            }
+            true
+        }
+        (RailVal::Quote(pattern), RailVal::Quote(quote)) => {
+            pattern.len() == quote.len()
+                && pattern
+                    .stack
+                    .values
+                    .iter()
+                    .zip(quote.stack.values.iter())
+                    .all(|(p, v)| bind(p, v, bindings))
+        }
+        (RailVal::Quote(pattern), RailVal::Stab(st)) => {
+            let pairs = pattern.stack.values.iter().collect::<Vec<_>>();
+            pairs.chunks(2).all(|pair| match pair {
+                [RailVal::String(k), p] => st.get(k).is_some_and(|v| bind(p, v, bindings)),
+                _ => false,
+            })
+        }
+        (RailVal::Quote(pattern), RailVal::Record(record)) => {
+            let (variant, fields) = match pattern.stack.values.iter().collect::<Vec<_>>()[..] {
+                [RailVal::Command(ref variant), ref fields @ ..] => {
+                    (variant.clone(), fields.to_vec())
+                }
+                _ => return false,
+            };
+            variant == record.variant
+                && fields.len() == record.fields.len()
+                && fields
+                    .into_iter()
+                    .zip(record.fields.iter())
+                    .all(|(p, (_, v))| bind(p, v, bindings))
+        }
+        (literal, value) => literal == value,
+    }
+}
+
+/// Match the values at the top of the stack against a list of patterns, one
+/// per value, so the last pattern matches the top value. On a mismatch,
+/// produces the value that didn't match.
+pub fn bind_top(patterns: &[RailVal], state: &RailState) -> Result<Bindings, Box<RailVal>> {
+    let values = state.stack.values.iter().skip(state.len() - patterns.len());
+    let mut bindings = vec![];
+
+    for (pattern, value) in patterns.iter().zip(values) {
+        if !bind(pattern, value, &mut bindings) {
+            return Err(Box::new(value.clone()));
+        }
+    }
+
+    Ok(bindings)
+}
+
+/// Define each binding as a command. When `expand` is set, quotes run their
+/// contents like `->`; otherwise every value is pushed as-is like `=>`. Binding
+/// a name that's already bound replaces it; other definitions are shadowed.
+pub fn define(state: RailState, bindings: Bindings, expand: bool, origin: &str) -> RailState {
+    let child = state.child();
+    let description = format!("A value bound by {}.", origin);
+    let source = state.origin_name();
+    state.update_stack_and_defs(|stack, mut definitions| {
+        for (name, value) in bindings.iter().cloned() {
+            let quote = if expand {
+                value.into_state(&child)
+            } else {
+                child.clone().push(value)
+            };
+            let def = RailDef::from_quote(&name, &description, quote)
+                .with_origin(&source)
+                .as_bound();
+            if definitions.get(&name).is_some_and(|def| def.bound) {
+                definitions.replace(name, def);
+            } else {
+                definitions.insert(name, def);
+            }
+        }
+        (stack, definitions)
+    })
+}
@@ -1,6 +1,7 @@
-use crate::tokens::Token;
+use crate::parser::Node;
+use crate::v1::loading::{self, Dialect};
+use crate::v1::log;
 use crate::v1::rail_machine::{RailRunResult, RailState, RunConventions};
-use crate::v1::{loading, log};
 use rustyline::error::ReadlineError;
 use rustyline::history::FileHistory;
 use rustyline::Editor;
@@ -11,6 +12,7 @@ pub struct RailPrompt {
     is_tty: bool,
     editor: RailRustylineEditor,
     conventions: &'static RunConventions<'static>,
+    dialect: Dialect,
 }
 
 impl RailPrompt {
@@ -22,10 +24,15 @@ impl RailPrompt {
             is_tty,
             editor,
             conventions,
+            dialect: Dialect::Rail,
         }
     }
 
-    pub fn run(self, state: RailState) -> RailRunResult {
+    pub fn with_dialect(self, dialect: Dialect) -> RailPrompt {
+        RailPrompt { dialect, ..self }
+    }
+
+    pub fn run(mut self, state: RailState) -> RailRunResult {
         log::info(
             state.conventions,
             format!(
@@ -34,26 +41,14 @@ impl RailPrompt {
             ),
         );
 
-        self.fold(Ok(state), |state, term| {
-            let result = state.unwrap_or_else(|_| unreachable!()).run_tokens(term);
-            let result = log::error_coerce(result);
-            Ok(result)
+        self.try_fold(state, |state, nodes| {
+            let result = state.run_nodes(nodes);
+            Ok(log::error_coerce(result))
         })
     }
-}
 
-impl Iterator for RailPrompt {
-    type Item = Vec<Token>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // If we're interactive with a human (at a TTY and not piped stdin),
-        // we pad with a newline in case the user uses print without newline.
-        // (Otherwise, the prompt will rewrite the line with output.)
-        if self.is_tty {
-            println!();
-        }
-
-        let input = self.editor.readline("> ");
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        let input = self.editor.readline(prompt);
 
         if let Err(e) = input {
             // ^D and ^C are not error cases.
@@ -76,6 +71,36 @@ impl Iterator for RailPrompt {
             false
         });
 
-        Some(loading::get_source_as_tokens(input))
+        Some(input)
+    }
+}
+
+impl Iterator for RailPrompt {
+    type Item = Vec<Node>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // If we're interactive with a human (at a TTY and not piped stdin),
+        // we pad with a newline in case the user uses print without newline.
+        // (Otherwise, the prompt will rewrite the line with output.)
+        if self.is_tty {
+            println!();
+        }
+
+        let mut source = self.read_line("> ")?;
+
+        loop {
+            match loading::parse_dialect(source.clone(), self.dialect) {
+                Ok(nodes) => return Some(nodes),
+                // Keep reading until an open quotation or string is closed.
+                Err(e) if e.is_incomplete() => {
+                    let line = self.read_line("| ")?;
+                    source = format!("{}\n{}", source, line);
+                }
+                Err(e) => {
+                    log::error(self.conventions, e);
+                    return Some(vec![]);
+                }
+            }
+        }
     }
 }
@@ -1,8 +1,15 @@
-use im::{HashMap, Vector};
+use im::{HashMap, OrdMap, Vector};
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::tokens::Token;
+use crate::parser::{Node, ParseError};
+use crate::tokens::{self, Token};
+use crate::v1::dtab::Dtab;
+use crate::v1::generator::Generator;
 use crate::v1::log;
 
 #[derive(Clone)]
@@ -13,14 +20,27 @@ pub struct RunConventions<'a> {
     pub warn_prefix: &'a str,
     pub error_prefix: &'a str,
     pub fatal_prefix: &'a str,
+    /// Make overriding a builtin or stdlib definition an error instead of a warning.
+    pub deny_redefine: bool,
 }
 
 #[derive(Clone)]
 pub enum RailError {
     UnknownCommand(String),
-    StackUnderflow(RailState, String, Vec<RailType>),
+    StackUnderflow(Box<RailState>, String, Vec<RailType>),
     TypeMismatch(Vec<RailType>, Vec<RailVal>),
     CantEscape(Context),
+    Parse(ParseError),
+    IndexOutOfBounds(String, i64, usize),
+    EmptyCollection(String),
+    MissingKey(String, String),
+    Redefinition(String, String),
+    PatternMismatch(String, Box<RailVal>),
+    NoMatchingMethod(String, Vec<Vec<RailType>>, Vec<RailVal>),
+    Overflow(String),
+    CantYield,
+    /// A file that couldn't be opened, and why.
+    CantOpen(String, String),
 }
 
 impl std::fmt::Debug for RailError {
@@ -35,6 +55,7 @@ impl std::fmt::Debug for RailError {
                     Context::Quotation { parent_state: _ } => "quotation",
                 }
             ),
+            Self::Parse(e) => write!(f, "{}", e),
             Self::StackUnderflow(state, name, consumes) => write!(
                 f,
                 "Stack underflow. Stack had {} elements, but {} wanted {}",
@@ -47,11 +68,54 @@ impl std::fmt::Debug for RailError {
                 write!(f, "Type mismatch. Wanted {:?} but had {:?}", types, values)
             }
             Self::UnknownCommand(cmd) => write!(f, "Unknown command: {}", cmd),
+            Self::IndexOutOfBounds(name, index, len) => write!(
+                f,
+                "Index out of bounds. {} wanted index {} but the quote had {} elements",
+                name, index, len
+            ),
+            Self::EmptyCollection(name) => {
+                write!(f, "Empty collection. {} wanted at least one element", name)
+            }
+            Self::MissingKey(name, key) => write!(
+                f,
+                "Missing key. {} wanted \"{}\" but the symbol table did not contain it",
+                name, key
+            ),
+            Self::NoMatchingMethod(name, signatures, values) => {
+                let values: Vec<RailType> = values.iter().map(|v| v.get_type()).collect();
+                write!(
+                    f,
+                    "Type mismatch. No method of {} accepts {:?}. Methods are: {:?}",
+                    name, values, signatures
+                )
+            }
+            Self::Redefinition(name, origin) => write!(
+                f,
+                "Redefinition. {} would override the definition from {}",
+                name, origin
+            ),
+            Self::Overflow(name) => write!(
+                f,
+                "Integer overflow. {} produced a value too large for an i64",
+                name
+            ),
+            Self::PatternMismatch(name, value) => {
+                write!(f, "Pattern mismatch. {} could not match {}", name, value)
+            }
+            Self::CantYield => write!(
+                f,
+                "Can't yield outside a generator. yield only works while a generator makes its next values."
+            ),
+            Self::CantOpen(path, reason) => write!(f, "Can't open {}. {}", path, reason),
         }
     }
 }
 
-pub type RailRunResult = Result<RailState, (RailState, RailError)>;
+/// A failed run: the state it failed in, kept so callers can recover and keep
+/// running, and what went wrong. Boxed, since states are large.
+pub type RailFailure = Box<(RailState, RailError)>;
+
+pub type RailRunResult = Result<RailState, RailFailure>;
 
 #[derive(Clone)]
 pub struct RailState {
@@ -61,6 +125,16 @@ pub struct RailState {
     // TODO: Save parents at time of definition and at runtime
     pub context: Context,
     pub conventions: &'static RunConventions<'static>,
+    pub rng: RailRng,
+    /// Documentation from `#:` comments, waiting for the next definition.
+    pub pending_doc: Option<String>,
+    /// The library being loaded, if any, recorded as the origin of new definitions.
+    pub origin: Option<String>,
+    /// The line of the source being run, when known.
+    pub line: Option<usize>,
+    /// Values given by `yield` while a generator makes its next values. Clones
+    /// share one buffer, so yields from nested commands are all collected.
+    pub yielded: Option<Rc<RefCell<Vector<RailVal>>>>,
 }
 
 impl RailState {
@@ -75,6 +149,11 @@ impl RailState {
             definitions,
             context,
             conventions,
+            rng: RailRng::from_time(),
+            pending_doc: None,
+            origin: None,
+            line: None,
+            yielded: None,
         }
     }
 
@@ -86,7 +165,7 @@ impl RailState {
         matches!(self.context, Context::Main)
     }
 
-    pub fn get_def(&self, name: &str) -> Option<RailDef> {
+    pub fn get_def(&self, name: &str) -> Option<RailDef<'_>> {
         self.definitions.get(name).cloned()
     }
 
@@ -96,25 +175,54 @@ impl RailState {
             definitions: self.definitions.clone(),
             context: Context::None,
             conventions: self.conventions,
+            rng: self.rng.clone(),
+            pending_doc: None,
+            origin: self.origin.clone(),
+            line: self.line,
+            yielded: self.yielded.clone(),
         }
     }
 
-    pub fn run_tokens(self, tokens: Vec<Token>) -> RailRunResult {
-        tokens.iter().fold(Ok(self), |state, term| {
-            state.and_then(|state| state.run_token(term.clone()))
-        })
+    pub fn run_nodes(self, nodes: Vec<Node>) -> RailRunResult {
+        let mut nodes = nodes.into_iter();
+        let mut state = self;
+
+        while let Some(node) = nodes.next() {
+            if let Node::Token(Token::Term(name), _) = &node {
+                if let Some(def) = state.get_immediate(name) {
+                    let rest = state.quote_nodes(nodes.collect());
+                    return state.expand(def, rest);
+                }
+            }
+            state = state.run_node(node)?;
+        }
+
+        Ok(state)
+    }
+
+    pub fn run_node(self, node: Node) -> RailRunResult {
+        match node {
+            Node::Token(token, span) => RailState {
+                line: Some(span.line),
+                ..self
+            }
+            .run_token(token),
+            Node::Quote(nodes, _) => self.deeper().run_nodes(nodes)?.higher(),
+        }
     }
 
     pub fn run_token(self, token: Token) -> RailRunResult {
         let res = match token {
-            Token::None => self,
-            Token::LeftBracket => self.deeper(),
-            Token::RightBracket => return self.higher(),
+            Token::None | Token::StackEffect(..) => self,
+            Token::LeftBracket | Token::RightBracket => {
+                unreachable!("The parser turns brackets into quotes")
+            }
             Token::String(s) => self.push_string(s),
             Token::Boolean(b) => self.push_bool(b),
             Token::I64(i) => self.push_i64(i),
             Token::F64(f) => self.push_f64(f),
             Token::DeferredTerm(term) => self.push_deferred_command(&term),
+            Token::DocComment(doc) => self.add_doc(&doc),
             Token::Term(term) => match (self.clone().get_def(&term), self.in_main()) {
                 (Some(op), true) => {
                     return op.act(self);
@@ -122,7 +230,10 @@ impl RailState {
                 (Some(op), false) => self.push_command(&op.name),
                 (None, false) => self.push_command(&term),
                 (None, true) => {
-                    return Err((self, RailError::UnknownCommand(term.replace('\n', "\\n"))));
+                    return Err(Box::new((
+                        self,
+                        RailError::UnknownCommand(term.replace('\n', "\\n")),
+                    )));
                 }
             },
         };
@@ -130,6 +241,81 @@ impl RailState {
         Ok(res)
     }
 
+    fn get_immediate(&self, name: &str) -> Option<RailDef<'static>> {
+        self.definitions
+            .get(name)
+            .filter(|def| def.immediate)
+            .cloned()
+    }
+
+    /// The nodes as a quote of values, without running any of them.
+    fn quote_nodes(&self, nodes: Vec<Node>) -> RailState {
+        nodes
+            .into_iter()
+            .fold(self.child(), |quote, node| match node {
+                Node::Quote(nodes, _) => {
+                    let subquote = quote.quote_nodes(nodes);
+                    quote.push_quote(subquote)
+                }
+                Node::Token(token, _) => match token {
+                    Token::Term(name) => quote.push_command(&name),
+                    Token::DeferredTerm(name) => quote.push_deferred_command(&name),
+                    Token::String(s) => quote.push_string(s),
+                    Token::Boolean(b) => quote.push_bool(b),
+                    Token::I64(i) => quote.push_i64(i),
+                    Token::F64(f) => quote.push_f64(f),
+                    comment @ (Token::DocComment(_) | Token::StackEffect(..)) => {
+                        quote.push(RailVal::Comment(comment))
+                    }
+                    Token::None | Token::LeftBracket | Token::RightBracket => quote,
+                },
+            })
+    }
+
+    /// Run an immediate command on a quote of the code that follows it, then
+    /// carry on with the quote of code it produces instead.
+    fn expand(self, def: RailDef<'static>, rest: RailState) -> RailRunResult {
+        let (code, state) = def.act(self.push_quote(rest))?.pop();
+        match code {
+            RailVal::Quote(code) => state.run_code(code.stack.values.into_iter().collect()),
+            value => Err(Box::new((
+                state.push(value.clone()),
+                RailError::TypeMismatch(vec![RailType::Quote], vec![value]),
+            ))),
+        }
+    }
+
+    /// Run values as if they had just been read: commands run at the top level
+    /// and are kept inside quotes, and immediate commands expand.
+    fn run_code(self, values: Vec<RailVal>) -> RailRunResult {
+        let mut values = values.into_iter();
+        let mut state = self;
+
+        while let Some(value) = values.next() {
+            if let RailVal::Comment(token) = value {
+                state = state.run_token(token)?;
+                continue;
+            }
+            if let RailVal::Command(name) = &value {
+                if let Some(def) = state.get_immediate(name) {
+                    let rest = values.fold(state.child(), |quote, value| quote.push(value));
+                    return state.expand(def, rest);
+                }
+            }
+
+            state = match value {
+                RailVal::Quote(quote) => {
+                    let values = quote.stack.values.into_iter().collect();
+                    state.deeper().run_code(values)?.higher()?
+                }
+                value if state.in_main() => state.clone().run_val(value, state.child())?,
+                value => state.push(value),
+            };
+        }
+
+        Ok(state)
+    }
+
     pub fn run_val(self, value: RailVal, local_state: RailState) -> RailRunResult {
         match value {
             RailVal::Command(name) => {
@@ -137,7 +323,7 @@ impl RailState {
                 let cmd = state.get_def(&name).or_else(|| local_state.get_def(&name));
 
                 match cmd {
-                    None => Err((self, RailError::UnknownCommand(name))),
+                    None => Err(Box::new((self, RailError::UnknownCommand(name)))),
                     Some(cmd) => cmd.act(self),
                 }
             }
@@ -147,19 +333,47 @@ impl RailState {
 
     pub fn run_in_state(self, other_state: RailState) -> RailRunResult {
         let values = self.stack.clone().values;
-        values
-            .into_iter()
-            .fold(Ok(other_state), |state, value| match state {
-                Ok(state) => state.run_val(value, self.child()),
-                err => err,
-            })
+        values.into_iter().try_fold(other_state, |state, value| {
+            state.run_val(value, self.child())
+        })
     }
 
     pub fn jailed_run_in_state(self, other_state: RailState) -> RailRunResult {
         let jailed = |state: RailState| other_state.clone().replace_stack(state.stack);
         self.run_in_state(other_state.clone())
             .map(jailed)
-            .map_err(|(state, e)| (jailed(state), e))
+            .map_err(|failure| {
+                let (state, e) = *failure;
+                Box::new((jailed(state), e))
+            })
+    }
+
+    fn add_doc(mut self, doc: &str) -> RailState {
+        self.pending_doc = Some(match self.pending_doc {
+            Some(pending) => format!("{} {}", pending, doc),
+            None => doc.to_string(),
+        });
+        self
+    }
+
+    pub fn with_origin(self, origin: Option<String>) -> RailState {
+        RailState { origin, ..self }
+    }
+
+    /// The origin to record for definitions made now, with the line being
+    /// run when loading a library.
+    pub fn origin_name(&self) -> String {
+        match (&self.origin, self.line) {
+            (Some(origin), Some(line)) => format!("{}:{}", origin, line),
+            (Some(origin), Option::None) => origin.clone(),
+            (Option::None, _) => "user".into(),
+        }
+    }
+
+    /// Take the documentation waiting for the next definition, if any.
+    pub fn take_doc(mut self) -> (Option<String>, RailState) {
+        let doc = self.pending_doc.take();
+        (doc, self)
     }
 
     pub fn update_stack(self, update: impl Fn(Stack) -> Stack) -> RailState {
@@ -168,6 +382,11 @@ impl RailState {
             definitions: self.definitions,
             context: self.context,
             conventions: self.conventions,
+            rng: self.rng,
+            pending_doc: self.pending_doc,
+            origin: self.origin,
+            line: self.line,
+            yielded: self.yielded,
         }
     }
 
@@ -181,6 +400,11 @@ impl RailState {
             definitions,
             context: self.context,
             conventions: self.conventions,
+            rng: self.rng,
+            pending_doc: self.pending_doc,
+            origin: self.origin,
+            line: self.line,
+            yielded: self.yielded,
         }
     }
 
@@ -190,6 +414,11 @@ impl RailState {
             definitions: self.definitions,
             context: self.context,
             conventions: self.conventions,
+            rng: self.rng,
+            pending_doc: self.pending_doc,
+            origin: self.origin,
+            line: self.line,
+            yielded: self.yielded,
         }
     }
 
@@ -199,6 +428,11 @@ impl RailState {
             definitions,
             context: self.context,
             conventions: self.conventions,
+            rng: self.rng,
+            pending_doc: self.pending_doc,
+            origin: self.origin,
+            line: self.line,
+            yielded: self.yielded,
         }
     }
 
@@ -208,11 +442,20 @@ impl RailState {
             definitions: self.definitions,
             context,
             conventions: self.conventions,
+            rng: self.rng,
+            pending_doc: self.pending_doc,
+            origin: self.origin,
+            line: self.line,
+            yielded: self.yielded,
         }
     }
 
     pub fn deeper(self) -> Self {
         let conventions = self.conventions;
+        let rng = self.rng.clone();
+        let origin = self.origin.clone();
+        let line = self.line;
+        let yielded = self.yielded.clone();
         RailState {
             stack: Stack::default(),
             definitions: self.definitions.clone(),
@@ -220,13 +463,18 @@ impl RailState {
                 parent_state: Box::new(self),
             },
             conventions,
+            rng,
+            pending_doc: None,
+            origin,
+            line,
+            yielded,
         }
     }
 
     pub fn higher(self) -> RailRunResult {
         match self.context.clone() {
             Context::Quotation { parent_state } => Ok(parent_state.push_quote(self)),
-            context => Err((self, RailError::CantEscape(context))),
+            context => Err(Box::new((self, RailError::CantEscape(context)))),
         }
     }
 
@@ -274,6 +522,22 @@ impl RailState {
         self.push(RailVal::Stab(st))
     }
 
+    pub fn push_dtab(self, dtab: Dtab<RailVal>) -> Self {
+        self.push(RailVal::Dtab(dtab))
+    }
+
+    pub fn push_generator(self, generator: Generator) -> Self {
+        self.push(RailVal::Generator(generator))
+    }
+
+    pub fn push_record(self, record: Record) -> Self {
+        self.push(RailVal::Record(record))
+    }
+
+    pub fn push_set(self, set: Set) -> Self {
+        self.push(RailVal::Set(set))
+    }
+
     pub fn push_string(self, s: String) -> Self {
         self.push(RailVal::String(s))
     }
@@ -282,6 +546,14 @@ impl RailState {
         self.push(RailVal::String(s.to_owned()))
     }
 
+    pub fn push_none(self) -> Self {
+        self.push(RailVal::None)
+    }
+
+    pub fn push_optional(self, value: Option<RailVal>) -> Self {
+        self.push(value.unwrap_or(RailVal::None))
+    }
+
     pub fn pop(self) -> (RailVal, Self) {
         let (value, stack) = self.stack.clone().pop();
         (value, self.replace_stack(stack))
@@ -330,6 +602,14 @@ impl RailState {
         }
     }
 
+    pub fn pop_generator(self, context: &str) -> (Generator, Self) {
+        let (value, quote) = self.pop();
+        match value {
+            RailVal::Generator(generator) => (generator, quote),
+            rail_val => panic!("{}", log::type_panic_msg(context, "generator", rail_val)),
+        }
+    }
+
     pub fn pop_stab(self, context: &str) -> (Stab, Self) {
         let (value, quote) = self.pop();
         match value {
@@ -340,6 +620,22 @@ impl RailState {
         }
     }
 
+    pub fn pop_dtab(self, context: &str) -> (Dtab<RailVal>, Self) {
+        let (value, quote) = self.pop();
+        match value {
+            RailVal::Dtab(d) => (d, quote),
+            rail_val => panic!("{}", log::type_panic_msg(context, "dtab", rail_val)),
+        }
+    }
+
+    pub fn pop_set(self, context: &str) -> (Set, Self) {
+        let (value, quote) = self.pop();
+        match value {
+            RailVal::Set(s) => (s, quote),
+            rail_val => panic!("{}", log::type_panic_msg(context, "set", rail_val)),
+        }
+    }
+
     pub fn pop_stab_entry(self, context: &str) -> (String, RailVal, Self) {
         let (original_entry, quote) = self.pop_quote(context);
         let (value, entry) = original_entry.clone().stack.pop();
@@ -381,7 +677,7 @@ pub enum Context {
     None,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum RailType {
     A,
     B,
@@ -399,8 +695,75 @@ pub enum RailType {
     Quote,
     QuoteOrCommand,
     QuoteOrString,
+    QuoteOrGenerator,
     String,
     Stab,
+    Set,
+    Dtab,
+    Generator,
+    /// A type declared in Rail with `record` or `variants`.
+    User(String),
+    /// The type of the `none` value, which stands in for an absent result.
+    Nothing,
+}
+
+impl RailType {
+    /// The builtin type with the given name, as written by `type` or in a signature.
+    pub fn from_name(name: &str) -> Option<RailType> {
+        use RailType::*;
+        let found = match name {
+            "a" | "_" => A,
+            "b" => B,
+            "c" => C,
+            "..." => Unknown,
+            "bool" => Boolean,
+            "num" => Number,
+            "i64" => I64,
+            "f64" => F64,
+            "command" => Command,
+            "quote" => Quote,
+            "quote|command" => QuoteOrCommand,
+            "quote|string" => QuoteOrString,
+            "quote|generator" => QuoteOrGenerator,
+            "string" => String,
+            "stab" => Stab,
+            "set" => Set,
+            "dtab" => Dtab,
+            "generator" => Generator,
+            "none" => Nothing,
+            _ => return Option::None,
+        };
+        Some(found)
+    }
+
+    /// The builtin or declared type with the given name. A type is declared
+    /// when some definition, like a `record` constructor, produces it.
+    pub fn named(name: &str, definitions: &Dictionary) -> Option<RailType> {
+        let user_type = RailType::User(name.to_string());
+        let declared = || {
+            definitions
+                .keys()
+                .filter_map(|k| definitions.get(k))
+                .any(|def| def.produces() == [user_type.clone()])
+        };
+        RailType::from_name(name).or_else(|| declared().then(|| user_type.clone()))
+    }
+
+    /// Whether a value can stand where this type is wanted.
+    pub fn accepts(&self, value: &RailVal) -> bool {
+        use RailType::*;
+        match self {
+            A | B | C | Unknown => true,
+            Number => matches!(value, RailVal::I64(_) | RailVal::F64(_)),
+            QuoteOrCommand => matches!(
+                value,
+                RailVal::Quote(_) | RailVal::Command(_) | RailVal::DeferredCommand(_)
+            ),
+            QuoteOrString => matches!(value, RailVal::Quote(_) | RailVal::String(_)),
+            QuoteOrGenerator => matches!(value, RailVal::Quote(_) | RailVal::Generator(_)),
+            expected => *expected == value.get_type(),
+        }
+    }
 }
 
 impl Display for RailType {
@@ -419,8 +782,14 @@ impl Display for RailType {
             Quote => "quote",
             QuoteOrCommand => "quote|command",
             QuoteOrString => "quote|string",
+            QuoteOrGenerator => "quote|generator",
             String => "string",
             Stab => "stab",
+            Set => "set",
+            Dtab => "dtab",
+            Generator => "generator",
+            User(name) => name,
+            Nothing => "none",
         };
 
         write!(fmt, "{}", my_type)
@@ -438,6 +807,14 @@ pub enum RailVal {
     Quote(RailState),
     String(String),
     Stab(Stab),
+    Set(Set),
+    Dtab(Dtab<RailVal>),
+    Record(Record),
+    Generator(Generator),
+    None,
+    /// A doc or stack effect comment, kept while its code is quoted for a
+    /// macro. It's read as the comment again when the code runs.
+    Comment(Token),
 }
 
 impl PartialEq for RailVal {
@@ -455,6 +832,13 @@ impl PartialEq for RailVal {
             // TODO: For quotes, what about differing dictionaries? For simple lists they don't matter, for closures they do.
             (Quote(a), Quote(b)) => a.stack == b.stack,
             (Stab(a), Stab(b)) => a == b,
+            (Set(a), Set(b)) => a == b,
+            (Dtab(a), Dtab(b)) => a == b,
+            (Record(a), Record(b)) => a == b,
+            // A generator is only equal to copies of itself.
+            (Generator(a), Generator(b)) => a.id() == b.id(),
+            (None, None) => true,
+            (Comment(a), Comment(b)) => a == b,
             _ => false,
         }
     }
@@ -465,7 +849,7 @@ impl RailVal {
         self.get_type().to_string()
     }
 
-    fn get_type(&self) -> RailType {
+    pub fn get_type(&self) -> RailType {
         match self {
             RailVal::Boolean(_) => RailType::Boolean,
             RailVal::I64(_) => RailType::I64,
@@ -475,6 +859,80 @@ impl RailVal {
             RailVal::Quote(_) => RailType::Quote,
             RailVal::String(_) => RailType::String,
             RailVal::Stab(_) => RailType::Stab,
+            RailVal::Set(_) => RailType::Set,
+            RailVal::Dtab(_) => RailType::Dtab,
+            RailVal::Generator(_) => RailType::Generator,
+            RailVal::Record(r) => RailType::User(r.type_name.clone()),
+            RailVal::None => RailType::Nothing,
+            RailVal::Comment(_) => RailType::Command,
+        }
+    }
+
+    /// Rail source that produces this value when evaluated.
+    pub fn to_source(&self) -> String {
+        match self {
+            RailVal::Boolean(_) | RailVal::I64(_) | RailVal::String(_) => self.to_string(),
+            RailVal::DeferredCommand(_) | RailVal::None => self.to_string(),
+            RailVal::F64(n) if n.is_nan() => "0.0 0.0 /".into(),
+            RailVal::F64(n) if n.is_infinite() => format!("{:?} 0.0 /", n.signum()),
+            RailVal::F64(n) => format!("{:?}", n),
+            RailVal::Command(name) => format!("[ {} ] unquote", name),
+            RailVal::Quote(q) => quote_source(&q.stack.values.iter().cloned().collect::<Vec<_>>()),
+            RailVal::Stab(st) => {
+                let entries = sorted_entries(st)
+                    .into_iter()
+                    .map(|(k, v)| quote_source(&[RailVal::String(k.clone()), v.clone()]))
+                    .collect::<Vec<_>>();
+                match entries.is_empty() {
+                    true => "stab".to_string(),
+                    false => format!("[ ] {} push from-entries", entries.join(" push ")),
+                }
+            }
+            RailVal::Set(set) => {
+                let members = set.values().cloned().collect::<Vec<_>>();
+                format!("{} set", quote_source(&members))
+            }
+            RailVal::Dtab(dtab) => {
+                let mut source = "dtab".to_string();
+                for k in dtab.sorted_keys() {
+                    for v in dtab.history(k).into_iter().rev() {
+                        let key = RailVal::String(k.clone());
+                        source.push_str(&format!(" {} {} dtab-set", key, v.to_source()));
+                    }
+                }
+                source
+            }
+            RailVal::Record(r) => {
+                let mut words = r
+                    .fields
+                    .iter()
+                    .map(|(_, v)| v.to_source())
+                    .collect::<Vec<_>>();
+                words.push(r.variant.clone());
+                words.join(" ")
+            }
+            RailVal::Generator(generator) => generator.to_source(),
+            // A doc comment runs to the end of its line.
+            RailVal::Comment(Token::DocComment(_)) => format!("{}\n", self),
+            RailVal::Comment(_) => self.to_string(),
+        }
+    }
+
+    /// Source for a value written inside a quote.
+    fn literal_source(&self) -> String {
+        match self {
+            RailVal::Command(name) => name.clone(),
+            value => value.to_source(),
+        }
+    }
+
+    /// Whether the value can be written inside a quote as-is.
+    fn is_literal(&self) -> bool {
+        match self {
+            RailVal::Boolean(_) | RailVal::I64(_) | RailVal::F64(_) | RailVal::String(_) => true,
+            RailVal::Command(_) | RailVal::DeferredCommand(_) | RailVal::Comment(_) => true,
+            RailVal::Quote(q) => q.stack.values.iter().all(|v| v.is_literal()),
+            _ => false,
         }
     }
 
@@ -512,16 +970,60 @@ impl std::fmt::Display for RailVal {
             Command(cmd) => write!(fmt, "{}", cmd),
             DeferredCommand(cmd) => write!(fmt, "\\{}", cmd),
             Quote(q) => write!(fmt, "{}", q.stack),
-            String(s) => write!(fmt, "\"{}\"", s.replace('\n', "\\n")),
+            String(s) => write!(fmt, "\"{}\"", tokens::escape(s)),
             Stab(t) => {
                 write!(fmt, "[ ").unwrap();
 
-                for (k, v) in t.iter() {
-                    write!(fmt, "[ \"{}\" {} ] ", k, v).unwrap();
+                for (k, v) in sorted_entries(t) {
+                    write!(fmt, "[ \"{}\" {} ] ", tokens::escape(k), v).unwrap();
                 }
 
                 write!(fmt, "]")
             }
+            Set(set) => {
+                write!(fmt, "[ ").unwrap();
+
+                for v in set.values() {
+                    write!(fmt, "{} ", v).unwrap();
+                }
+
+                write!(fmt, "]")
+            }
+            Dtab(dtab) => {
+                write!(fmt, "[ ").unwrap();
+
+                for k in dtab.sorted_keys() {
+                    write!(fmt, "[ \"{}\" [ ", k).unwrap();
+                    for v in dtab.history(k) {
+                        write!(fmt, "{} ", v).unwrap();
+                    }
+                    write!(fmt, "] ] ").unwrap();
+                }
+
+                write!(fmt, "]")
+            }
+            Record(r) => {
+                write!(fmt, "{} {{ ", r.variant).unwrap();
+
+                for (k, v) in r.fields.iter() {
+                    write!(fmt, "{}: {} ", k, v).unwrap();
+                }
+
+                write!(fmt, "}}")
+            }
+            Generator(_) => write!(fmt, "generator"),
+            None => write!(fmt, "none"),
+            Comment(Token::DocComment(doc)) => write!(fmt, "#: {}", doc),
+            Comment(Token::StackEffect(consumes, produces)) => {
+                let names = |names: &[std::string::String]| {
+                    names
+                        .iter()
+                        .map(|name| format!("{} ", name))
+                        .collect::<std::string::String>()
+                };
+                write!(fmt, "( {}-- {})", names(consumes), names(produces))
+            }
+            Comment(token) => write!(fmt, "{:?}", token),
         }
     }
 }
@@ -534,11 +1036,13 @@ pub struct Stack {
 impl PartialEq for Stack {
     // FIXME: Not equal if inequal shadows (same name, diff binding) exist in the values
     fn eq(&self, other: &Self) -> bool {
-        self.values
-            .clone()
-            .into_iter()
-            .zip(other.values.clone())
-            .all(|(a, b)| a == b)
+        self.len() == other.len()
+            && self
+                .values
+                .clone()
+                .into_iter()
+                .zip(other.values.clone())
+                .all(|(a, b)| a == b)
     }
 }
 
@@ -683,14 +1187,16 @@ impl std::fmt::Display for Stack {
     }
 }
 
-pub type Dictionary = HashMap<String, RailDef<'static>>;
+pub type Dictionary = Dtab<RailDef<'static>>;
 
 pub fn dictionary_of<Entries>(entries: Entries) -> Dictionary
 where
     Entries: IntoIterator<Item = RailDef<'static>>,
 {
-    let entries = entries.into_iter().map(|def| (def.name.clone(), def));
-    HashMap::from_iter(entries)
+    entries
+        .into_iter()
+        .map(|def| (def.name.clone(), def))
+        .collect()
 }
 
 pub type Stab = HashMap<String, RailVal>;
@@ -699,12 +1205,221 @@ pub fn new_stab() -> Stab {
     HashMap::new()
 }
 
+/// A set of values, ordered by `SetKey` so that values which are equal (like
+/// `1` and `1.0`) are only stored once.
+pub type Set = OrdMap<SetKey, RailVal>;
+
+pub fn new_set() -> Set {
+    OrdMap::new()
+}
+
+pub fn set_key(value: &RailVal) -> SetKey {
+    SetKey(value.clone())
+}
+
+/// A value ordered first by its type, then by its contents: numbers by value,
+/// strings and commands alphabetically, and collections member by member.
+/// Generators are ordered by identity.
+#[derive(Clone)]
+pub struct SetKey(RailVal);
+
+impl SetKey {
+    fn rank(value: &RailVal) -> u8 {
+        match value {
+            RailVal::None => 0,
+            RailVal::Boolean(_) => 1,
+            RailVal::I64(_) | RailVal::F64(_) => 2,
+            RailVal::String(_) => 3,
+            RailVal::Command(_) => 4,
+            RailVal::DeferredCommand(_) => 5,
+            RailVal::Quote(_) => 6,
+            RailVal::Stab(_) => 7,
+            RailVal::Set(_) => 8,
+            RailVal::Dtab(_) => 9,
+            RailVal::Record(_) => 10,
+            RailVal::Generator(_) => 11,
+            RailVal::Comment(_) => 12,
+        }
+    }
+
+    fn compare(a: &RailVal, b: &RailVal) -> Ordering {
+        use RailVal::*;
+
+        // Equal numbers of either type are the same member, and zero has no sign.
+        let float = |n: f64| if n == 0.0 { 0.0 } else { n };
+        let values = |a: &RailVal, b: &RailVal| SetKey::compare(a, b);
+        let entries = |(ak, av): (&std::string::String, &RailVal), (bk, bv): (_, _)| {
+            ak.cmp(bk).then_with(|| SetKey::compare(av, bv))
+        };
+
+        match (a, b) {
+            (Boolean(a), Boolean(b)) => a.cmp(b),
+            (I64(a), I64(b)) => a.cmp(b),
+            (I64(a), F64(b)) => float(*a as f64).total_cmp(&float(*b)),
+            (F64(a), I64(b)) => float(*a).total_cmp(&float(*b as f64)),
+            (F64(a), F64(b)) => float(*a).total_cmp(&float(*b)),
+            (String(a), String(b)) => a.cmp(b),
+            (Command(a), Command(b)) => a.cmp(b),
+            (DeferredCommand(a), DeferredCommand(b)) => a.cmp(b),
+            (Quote(a), Quote(b)) => compare_by(&a.stack.values, &b.stack.values, values),
+            (Stab(a), Stab(b)) => compare_by(sorted_entries(a), sorted_entries(b), entries),
+            (Set(a), Set(b)) => a.keys().cmp(b.keys()),
+            (Dtab(a), Dtab(b)) => compare_by(dtab_entries(a), dtab_entries(b), entries),
+            (Record(a), Record(b)) => (&a.type_name, &a.variant)
+                .cmp(&(&b.type_name, &b.variant))
+                .then_with(|| {
+                    let a = a.fields.iter().map(|(k, v)| (k, v));
+                    let b = b.fields.iter().map(|(k, v)| (k, v));
+                    compare_by(a, b, entries)
+                }),
+            (Generator(a), Generator(b)) => a.id().cmp(&b.id()),
+            (Comment(a), Comment(b)) => format!("{:?}", a).cmp(&format!("{:?}", b)),
+            (a, b) => SetKey::rank(a).cmp(&SetKey::rank(b)),
+        }
+    }
+}
+
+/// Every value a dtab holds for each key, with keys in order.
+fn dtab_entries(dtab: &crate::v1::dtab::Dtab<RailVal>) -> Vec<(&std::string::String, &RailVal)> {
+    dtab.sorted_keys()
+        .into_iter()
+        .flat_map(|k| dtab.history(k).into_iter().map(move |v| (k, v)))
+        .collect()
+}
+
+/// Compare two sequences item by item, and then by length.
+fn compare_by<A: IntoIterator, B: IntoIterator>(
+    a: A,
+    b: B,
+    compare: impl Fn(A::Item, B::Item) -> Ordering,
+) -> Ordering {
+    let (mut a, mut b) = (a.into_iter(), b.into_iter());
+    loop {
+        match (a.next(), b.next()) {
+            (Some(a), Some(b)) => match compare(a, b) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            },
+            (a, b) => return a.is_some().cmp(&b.is_some()),
+        }
+    }
+}
+
+impl PartialEq for SetKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for SetKey {}
+
+impl PartialOrd for SetKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SetKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        SetKey::compare(&self.0, &other.0)
+    }
+}
+
+/// A value of a type declared in Rail. Plain records have a single variant
+/// named after the type; sum types have one variant per alternative.
+#[derive(Clone, PartialEq)]
+pub struct Record {
+    pub type_name: String,
+    pub variant: String,
+    pub fields: Vector<(String, RailVal)>,
+}
+
+impl Record {
+    pub fn get(&self, field: &str) -> Option<&RailVal> {
+        self.fields.iter().find(|(k, _)| k == field).map(|(_, v)| v)
+    }
+
+    /// Whether it's the given variant of the given type. Variants of different
+    /// types may share a name.
+    pub fn is(&self, type_name: &str, variant: &str) -> bool {
+        self.type_name == type_name && self.variant == variant
+    }
+}
+
+/// A symbol table's entries ordered by key, for deterministic output.
+pub fn sorted_entries(st: &Stab) -> Vec<(&String, &RailVal)> {
+    let mut entries = st.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(k, _)| *k);
+    entries
+}
+
+/// A seedable pseudo-random number generator (SplitMix64). Clones share one
+/// sequence, so every state derived from a program sees the same stream and a
+/// reseed anywhere affects all of them.
+#[derive(Clone)]
+pub struct RailRng {
+    state: Rc<Cell<u64>>,
+}
+
+impl RailRng {
+    pub fn new(seed: u64) -> Self {
+        RailRng {
+            state: Rc::new(Cell::new(seed)),
+        }
+    }
+
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|t| t.as_nanos() as u64)
+            .unwrap_or_default();
+        RailRng::new(nanos)
+    }
+
+    pub fn reseed(&self, seed: u64) {
+        self.state.set(seed);
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A float in the range [0, 1).
+    pub fn next_f64(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An integer in the range [0, bound). The bound must not be zero.
+    pub fn below(&self, bound: u64) -> u64 {
+        // Reject the top sliver of values that would bias the modulo.
+        let zone = u64::MAX - (u64::MAX % bound);
+        loop {
+            let n = self.next_u64();
+            if n < zone {
+                return n % bound;
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct RailDef<'a> {
     pub name: String,
     pub description: String,
-    consumes: &'a [RailType],
-    produces: &'a [RailType],
+    /// Where the definition came from, like `corelib/math` or `stdlib/math.rail:12`.
+    pub origin: String,
+    /// Whether the command runs as soon as it's read, to rewrite the code after it.
+    pub immediate: bool,
+    /// Whether a pattern like `->` bound it, so binding the name again replaces
+    /// it rather than shadowing it.
+    pub bound: bool,
+    consumes: Vec<RailType>,
+    produces: Vec<RailType>,
     action: RailAction<'a>,
 }
 
@@ -713,14 +1428,86 @@ pub enum RailAction<'a> {
     Builtin(Arc<dyn Fn(RailState) -> RailRunResult + 'a>),
     BuiltinSafe(Arc<dyn Fn(RailState) -> RailState + 'a>),
     Quotation(RailState),
+    /// A quotation whose declared signature is checked when it starts and ends.
+    TypedQuotation(RailState),
+    /// Methods chosen by the types at the top of the stack, newest first, and
+    /// the definition to fall back on when none of them apply.
+    Generic(Vec<Method>, Option<Box<RailDef<'a>>>),
+}
+
+/// Source for a quote of values. Values that can't be written inside a quote,
+/// like symbol tables, are built up and pushed onto it instead.
+fn quote_source(values: &[RailVal]) -> String {
+    if values.iter().all(|v| v.is_literal()) {
+        let words = values
+            .iter()
+            .map(|v| v.literal_source())
+            .collect::<Vec<_>>();
+        return match words.is_empty() {
+            true => "[ ]".to_string(),
+            false => format!("[ {} ]", words.join(" ")),
+        };
+    }
+
+    values.iter().fold("[ ]".to_string(), |source, v| {
+        format!("{} {} push", source, v.to_source())
+    })
+}
+
+/// The body of a quote made by `def`, which wraps it as `[ body ] do`.
+fn unwrap_do(quote: &RailState) -> Option<String> {
+    match quote.stack.values.iter().collect::<Vec<_>>()[..] {
+        [RailVal::Quote(body), RailVal::Command(ref cmd)] if cmd == "do" => {
+            Some(body.stack.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Check that the values at the top of the stack have the given types.
+fn check_top(state: RailState, types: &[RailType]) -> RailRunResult {
+    let top = state.stack.values.iter().skip(state.len() - types.len());
+    if types.iter().zip(top).all(|(t, v)| t.accepts(v)) {
+        return Ok(state);
+    }
+
+    let values = state
+        .stack
+        .values
+        .iter()
+        .skip(state.len() - types.len())
+        .cloned()
+        .collect();
+    Err(Box::new((
+        state,
+        RailError::TypeMismatch(types.to_vec(), values),
+    )))
+}
+
+#[derive(Clone)]
+pub struct Method {
+    pub signature: Vec<RailType>,
+    pub body: RailState,
+}
+
+impl Method {
+    fn accepts(&self, stack: &Stack) -> bool {
+        let n = self.signature.len();
+        n <= stack.len()
+            && self
+                .signature
+                .iter()
+                .zip(stack.values.iter().skip(stack.len() - n))
+                .all(|(t, v)| t.accepts(v))
+    }
 }
 
 impl<'a> RailDef<'a> {
     pub fn on_state<F>(
         name: &str,
         description: &str,
-        consumes: &'a [RailType],
-        produces: &'a [RailType],
+        consumes: &[RailType],
+        produces: &[RailType],
         state_action: F,
     ) -> RailDef<'a>
     where
@@ -729,8 +1516,11 @@ impl<'a> RailDef<'a> {
         RailDef {
             name: name.to_string(),
             description: description.to_string(),
-            consumes,
-            produces,
+            origin: String::new(),
+            immediate: false,
+            bound: false,
+            consumes: consumes.to_vec(),
+            produces: produces.to_vec(),
             action: RailAction::Builtin(Arc::new(state_action)),
         }
     }
@@ -739,8 +1529,8 @@ impl<'a> RailDef<'a> {
     pub fn on_state_noerr<F>(
         name: &str,
         description: &str,
-        consumes: &'a [RailType],
-        produces: &'a [RailType],
+        consumes: &[RailType],
+        produces: &[RailType],
         state_action: F,
     ) -> RailDef<'a>
     where
@@ -749,8 +1539,11 @@ impl<'a> RailDef<'a> {
         RailDef {
             name: name.to_string(),
             description: description.to_string(),
-            consumes,
-            produces,
+            origin: String::new(),
+            immediate: false,
+            bound: false,
+            consumes: consumes.to_vec(),
+            produces: produces.to_vec(),
             action: RailAction::BuiltinSafe(Arc::new(state_action)),
         }
     }
@@ -758,8 +1551,8 @@ impl<'a> RailDef<'a> {
     pub fn on_jailed_state<F>(
         name: &str,
         description: &str,
-        consumes: &'a [RailType],
-        produces: &'a [RailType],
+        consumes: &[RailType],
+        produces: &[RailType],
         state_action: F,
     ) -> RailDef<'a>
     where
@@ -768,8 +1561,11 @@ impl<'a> RailDef<'a> {
         RailDef {
             name: name.to_string(),
             description: description.to_string(),
-            consumes,
-            produces,
+            origin: String::new(),
+            immediate: false,
+            bound: false,
+            consumes: consumes.to_vec(),
+            produces: produces.to_vec(),
             action: RailAction::Builtin(Arc::new(move |state| {
                 let definitions = state.definitions.clone();
                 let substate = state_action(state)?;
@@ -781,8 +1577,8 @@ impl<'a> RailDef<'a> {
     pub fn contextless<F>(
         name: &str,
         description: &str,
-        consumes: &'a [RailType],
-        produces: &'a [RailType],
+        consumes: &[RailType],
+        produces: &[RailType],
         contextless_action: F,
     ) -> RailDef<'a>
     where
@@ -799,19 +1595,108 @@ impl<'a> RailDef<'a> {
         RailDef {
             name: name.to_string(),
             description: description.to_string(),
-            consumes: &[],
-            produces: &[],
+            origin: String::new(),
+            immediate: false,
+            bound: false,
+            consumes: vec![],
+            produces: vec![],
             action: RailAction::Quotation(quote),
         }
     }
 
+    pub fn from_typed_quote(
+        name: &str,
+        description: &str,
+        consumes: Vec<RailType>,
+        produces: Vec<RailType>,
+        quote: RailState,
+    ) -> RailDef<'a> {
+        RailDef {
+            name: name.to_string(),
+            description: description.to_string(),
+            origin: String::new(),
+            immediate: false,
+            bound: false,
+            consumes,
+            produces,
+            action: RailAction::TypedQuotation(quote),
+        }
+    }
+
+    /// The declared stack effect, like `[ i64 string -- string ]`, if there is one.
+    pub fn signature(&self) -> Option<String> {
+        if self.consumes.is_empty() && self.produces.is_empty() {
+            return None;
+        }
+
+        let names =
+            |types: &[RailType]| -> Vec<String> { types.iter().map(|t| t.to_string()).collect() };
+        let mut words = names(&self.consumes);
+        words.push("--".into());
+        words.extend(names(&self.produces));
+        Some(format!("[ {} ]", words.join(" ")))
+    }
+
+    /// Rail source that would make this definition, or a note that it's a builtin.
+    pub fn source(&self) -> String {
+        let origin = if self.origin.is_empty() {
+            "unknown"
+        } else {
+            &self.origin
+        };
+        let header = format!("# {} from {}", self.name, origin);
+
+        let body = match &self.action {
+            RailAction::Builtin(_) | RailAction::BuiltinSafe(_) => match self.signature() {
+                Some(signature) => format!("builtin (Rust) {}", signature),
+                None => "builtin (Rust)".to_string(),
+            },
+            RailAction::Quotation(quote) => match unwrap_do(quote) {
+                Some(body) => format!("{} [ {} ] def", body, self.name),
+                None => format!("{} [ {} ] def!", quote.stack, self.name),
+            },
+            RailAction::TypedQuotation(quote) => {
+                let signature = self.signature().unwrap_or_default();
+                match unwrap_do(quote) {
+                    Some(body) => format!("{} [ {} ] {} def-typed", body, self.name, signature),
+                    None => format!("{} [ {} ] {} def-typed!", quote.stack, self.name, signature),
+                }
+            }
+            RailAction::Generic(methods, fallback) => {
+                let mut lines = methods
+                    .iter()
+                    .rev()
+                    .map(|m| {
+                        let types: Vec<String> =
+                            m.signature.iter().map(|t| t.to_string()).collect();
+                        format!(
+                            "{} [ {} ] \"{}\" defmethod",
+                            m.body.stack,
+                            types.join(" "),
+                            self.name
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(fallback) = fallback {
+                    lines.insert(0, format!("# Otherwise:\n{}", fallback.source()));
+                }
+                lines.join("\n")
+            }
+        };
+
+        match self.immediate {
+            true => format!("{}\n{}\n\"{}\" immediate", header, body, self.name),
+            false => format!("{}\n{}", header, body),
+        }
+    }
+
     pub fn act(self, state: RailState) -> RailRunResult {
         if state.stack.len() < self.consumes.len() {
             // TODO: At some point will want source context here like line/column number.
-            return Err((
+            return Err(Box::new((
                 state.clone(),
-                RailError::StackUnderflow(state, self.name, self.consumes.to_vec()),
-            ));
+                RailError::StackUnderflow(Box::new(state), self.name, self.consumes),
+            )));
         }
 
         // TODO: Type checks?
@@ -820,9 +1705,128 @@ impl<'a> RailDef<'a> {
             RailAction::Builtin(action) => action(state),
             RailAction::BuiltinSafe(action) => Ok(action(state)),
             RailAction::Quotation(quote) => quote.run_in_state(state),
+            RailAction::TypedQuotation(quote) => {
+                check_top(state.clone(), &self.consumes)?;
+                let depth = state.len() - self.consumes.len() + self.produces.len();
+                let state = quote.run_in_state(state)?;
+                if state.len() != depth {
+                    let values = state
+                        .stack
+                        .values
+                        .iter()
+                        .skip(depth.min(state.len()))
+                        .cloned()
+                        .collect();
+                    return Err(Box::new((
+                        state,
+                        RailError::TypeMismatch(self.produces, values),
+                    )));
+                }
+                check_top(state, &self.produces)
+            }
+            RailAction::Generic(methods, fallback) => {
+                if let Some(method) = methods.iter().find(|m| m.accepts(&state.stack)) {
+                    return method.body.clone().run_in_state(state);
+                }
+
+                if let Some(fallback) = fallback {
+                    return fallback.act(state);
+                }
+
+                let wanted = methods.iter().map(|m| m.signature.len()).max().unwrap_or(0);
+                let top = state.stack.len().saturating_sub(wanted);
+                let values = state.stack.values.iter().skip(top).cloned().collect();
+                let signatures = methods.into_iter().map(|m| m.signature).collect();
+                Err(Box::new((
+                    state,
+                    RailError::NoMatchingMethod(self.name, signatures, values),
+                )))
+            }
+        }
+    }
+
+    /// Add a method for the given signature. A definition that isn't generic
+    /// yet becomes the fallback for values no method accepts.
+    pub fn with_method(self, signature: Vec<RailType>, body: RailState) -> RailDef<'a> {
+        let (mut methods, fallback) = match self.action.clone() {
+            RailAction::Generic(methods, fallback) => (methods, fallback),
+            _ => (vec![], Some(Box::new(self.clone()))),
+        };
+
+        methods.retain(|m| m.signature != signature);
+        methods.insert(0, Method { signature, body });
+
+        let signatures = methods
+            .iter()
+            .map(|m| {
+                let types: Vec<String> = m.signature.iter().map(|t| t.to_string()).collect();
+                format!("[ {} ]", types.join(" "))
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        RailDef {
+            name: self.name.clone(),
+            description: format!("Generic command with methods for {}.", signatures),
+            origin: self.origin.clone(),
+            immediate: self.immediate,
+            bound: false,
+            consumes: vec![],
+            produces: vec![],
+            action: RailAction::Generic(methods, fallback),
+        }
+    }
+
+    pub fn generic(name: &str) -> RailDef<'a> {
+        RailDef {
+            name: name.to_string(),
+            description: String::new(),
+            origin: String::new(),
+            immediate: false,
+            bound: false,
+            consumes: vec![],
+            produces: vec![],
+            action: RailAction::Generic(vec![], None),
+        }
+    }
+
+    pub fn with_origin(self, origin: &str) -> RailDef<'a> {
+        RailDef {
+            origin: origin.to_string(),
+            ..self
+        }
+    }
+
+    /// The origin without the line it was defined on, like `stdlib/math.rail`.
+    pub fn module(&self) -> &str {
+        match self.origin.rsplit_once(':') {
+            Some((module, line)) if line.parse::<usize>().is_ok() => module,
+            _ => &self.origin,
+        }
+    }
+
+    pub fn as_immediate(self) -> RailDef<'a> {
+        RailDef {
+            immediate: true,
+            ..self
+        }
+    }
+
+    pub fn as_bound(self) -> RailDef<'a> {
+        RailDef {
+            bound: true,
+            ..self
         }
     }
 
+    pub fn consumes(&self) -> &[RailType] {
+        &self.consumes
+    }
+
+    pub fn produces(&self) -> &[RailType] {
+        &self.produces
+    }
+
     pub fn rename<F>(self, f: F) -> RailDef<'a>
     where
         F: Fn(String) -> String,
@@ -830,6 +1834,9 @@ impl<'a> RailDef<'a> {
         RailDef {
             name: f(self.name),
             description: self.description,
+            origin: self.origin,
+            immediate: self.immediate,
+            bound: self.bound,
             consumes: self.consumes,
             produces: self.produces,
             action: self.action,
@@ -843,6 +1850,9 @@ impl<'a> RailDef<'a> {
         RailDef {
             name: self.name,
             description: f(self.description),
+            origin: self.origin,
+            immediate: self.immediate,
+            bound: self.bound,
             consumes: self.consumes,
             produces: self.produces,
             action: self.action,
@@ -98,6 +98,12 @@ fn test_quote_of_many_types_equality() {
     );
 }
 
+#[test]
+fn test_quotes_of_different_lengths_are_not_equal() {
+    assert_eq!("false", &rail_oneliner("[ 1 2 ] [ 1 ] eq? print").stdout);
+    assert_eq!("false", &rail_oneliner("[ 1 ] [ 1 2 ] eq? print").stdout);
+}
+
 #[test]
 fn test_comparison_gt() {
     assert_eq!("true", &rail_oneliner("1 2 gt? print").stdout);
@@ -162,3 +168,32 @@ fn test_comparison_lte() {
     assert_eq!("false", &rail_oneliner("1 1.1 lte? print").stdout);
     assert_eq!("false", &rail_oneliner("0.9 1.1 lte? print").stdout);
 }
+
+#[test]
+fn test_not_any() {
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ 1 3 5 ] [ even? ] not-any? print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ 1 2 5 ] [ even? ] not-any? print").stdout
+    );
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ ] [ even? ] not-any? print").stdout
+    );
+}
+
+#[test]
+fn test_none_still_tests_lists() {
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ 1 3 5 ] [ even? ] none print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ 1 2 5 ] [ even? ] none print").stdout
+    );
+    assert_eq!("none", &rail_oneliner("1 none print").stdout);
+}
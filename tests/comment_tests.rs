@@ -0,0 +1,36 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_line_comment() {
+    assert_eq!("3", &rail_oneliner("1 2 + print # 4 print\n").stdout);
+}
+
+#[test]
+fn test_shebang() {
+    assert_eq!(
+        "ok",
+        &rail_oneliner("#!/usr/bin/env rail\n\"ok\" print").stdout
+    );
+}
+
+#[test]
+fn test_block_comment() {
+    let res = rail_oneliner("1 #[ 2 print\n#[ nested ]# 3 print ]# print");
+    assert_eq!("1", &res.stdout);
+}
+
+#[test]
+fn test_hash_words() {
+    assert_eq!(
+        "tagged",
+        &rail_oneliner("[ \"tagged\" ] [ #tag ] def #tag print").stdout
+    );
+}
+
+#[test]
+fn test_stack_effect_comment() {
+    let res = rail_oneliner("[ ( a b -- c ) + ] [ add ] def ( just a note ) 1 2 add print");
+    assert_eq!("3", &res.stdout);
+}
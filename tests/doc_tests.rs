@@ -0,0 +1,48 @@
+mod rail_runner;
+
+use rail_runner::{rail, rail_oneliner};
+
+#[test]
+fn test_undocumented_by_default() {
+    assert_eq!(
+        "Undocumented.",
+        &rail_oneliner("[ 2 * ] [ double ] def [ double ] describe print").stdout
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    let source = r#"
+        #: Consume a number, and produce it doubled.
+        #: Works on integers and floats.
+        [ 2 * ] [ double ] def
+
+        [ 3 * ] [ triple ] def
+
+        [ double ] describe println
+        [ triple ] describe println
+    "#;
+
+    let res = rail(&[source]);
+
+    assert_eq!("", res.stderr);
+    assert_eq!(
+        "Consume a number, and produce it doubled. Works on integers and floats.\nUndocumented.\n",
+        res.stdout
+    );
+}
+
+#[test]
+fn test_doc_word() {
+    let program =
+        "[ 2 * ] [ double ] def [ double ] \"Doubles a number.\" doc [ double ] describe print";
+    assert_eq!("Doubles a number.", &rail_oneliner(program).stdout);
+}
+
+#[test]
+fn test_bindings_are_described() {
+    assert_eq!(
+        "A value bound by ->.",
+        &rail_oneliner("1 [ x ] -> [ x ] describe print").stdout
+    );
+}
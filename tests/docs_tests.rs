@@ -0,0 +1,37 @@
+mod rail_runner;
+
+use rail_runner::{rail_oneliner, railsh_args};
+
+#[test]
+fn test_markdown_reference() {
+    let res = railsh_args(&["docs"]);
+
+    assert_eq!("", res.stderr);
+    assert!(res.stdout.starts_with("# Rail reference\n"));
+    assert!(res.stdout.contains("\n## corelib/math\n"));
+    assert!(res.stdout.contains("\n## stdlib/math.rail\n"));
+    assert!(res.stdout.contains("\n### `sqrt`\n\n`[ num -- f64 ]`\n"));
+}
+
+#[test]
+fn test_html_reference() {
+    let res = railsh_args(&["docs", "--format", "html"]);
+
+    assert_eq!("", res.stderr);
+    assert!(res.stdout.starts_with("<!DOCTYPE html>"));
+    assert!(res
+        .stdout
+        .contains("<h2 id=\"corelib/math\">corelib/math</h2>"));
+    assert!(res.stdout.contains("<dt><code>gt?</code>"));
+}
+
+#[test]
+fn test_apropos() {
+    assert_eq!(
+        "[ \"dtab-history\" ]",
+        &rail_oneliner("\"newest first\" apropos print").stdout
+    );
+    assert!(rail_oneliner("\"SHUFFLE\" apropos print")
+        .stdout
+        .contains("\"shuffle\""));
+}
@@ -0,0 +1,52 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_dtab_get_latest() {
+    assert_eq!(
+        "2",
+        &rail_oneliner("dtab \"k\" 1 dtab-set \"k\" 2 dtab-set \"k\" dtab-get print").stdout
+    );
+}
+
+#[test]
+fn test_dtab_unset_restores_shadowed() {
+    assert_eq!(
+        "1",
+        &rail_oneliner(
+            "dtab \"k\" 1 dtab-set \"k\" 2 dtab-set \"k\" dtab-unset \"k\" dtab-get print"
+        )
+        .stdout
+    );
+}
+
+#[test]
+fn test_dtab_history() {
+    assert_eq!(
+        "[ \"second\" \"first\" ]",
+        &rail_oneliner(
+            "dtab \"k\" \"first\" dtab-set \"k\" \"second\" dtab-set \"k\" dtab-history print"
+        )
+        .stdout
+    );
+    assert_eq!(
+        "[ ]",
+        &rail_oneliner("dtab \"k\" dtab-history print").stdout
+    );
+}
+
+#[test]
+fn test_dtab_missing_key() {
+    let res = rail_oneliner("dtab \"k\" 1 dtab-set \"k\" dtab-unset \"k\" dtab-get");
+    assert!(res.stderr.contains("Missing key"));
+}
+
+#[test]
+fn test_dtab_print_and_type() {
+    assert_eq!(
+        "[ [ \"a\" [ 2 1 ] ] [ \"b\" [ 3 ] ] ]",
+        &rail_oneliner("dtab \"b\" 3 dtab-set \"a\" 1 dtab-set \"a\" 2 dtab-set print").stdout
+    );
+    assert_eq!("dtab", &rail_oneliner("dtab type print").stdout);
+}
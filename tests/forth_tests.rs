@@ -0,0 +1,102 @@
+mod rail_runner;
+
+use rail_runner::{rail, railsh_args, railsh_run_file};
+
+fn forth(code: &str) -> rail_runner::RailRunResult {
+    rail(&["--dialect", "forth", code])
+}
+
+#[test]
+fn test_colon_definitions() {
+    assert_eq!("49 ", &forth(": SQUARE DUP * ; 7 square .").stdout);
+}
+
+#[test]
+fn test_if_else_then() {
+    let res = forth(": sign DUP 0< IF DROP .\" neg\" ELSE 0= IF .\" zero\" ELSE .\" pos\" THEN THEN ; -3 sign 0 sign 4 sign");
+    assert_eq!("negzeropos", &res.stdout);
+}
+
+#[test]
+fn test_do_loop() {
+    assert_eq!("0 1 2 3 4 ", &forth("5 0 DO I . LOOP").stdout);
+}
+
+#[test]
+fn test_nested_do_loops_keep_their_own_index() {
+    assert_eq!(
+        "0 1 2 \n",
+        &forth(": t 3 0 DO 2 0 DO LOOP I . LOOP CR ; t").stdout
+    );
+    assert_eq!(
+        "0 1 2 ",
+        &forth(": inner 2 0 DO LOOP ; : t 3 0 DO inner I . LOOP ; t").stdout
+    );
+}
+
+#[test]
+fn test_variables_and_constants() {
+    let res = forth("VARIABLE x 5 x ! 3 x +! x ? x @ 2 * . 10 CONSTANT ten ten .");
+    assert_eq!("8 16 10 ", &res.stdout);
+}
+
+#[test]
+fn test_storing_variables_is_not_redefining() {
+    let res = rail(&[
+        "--deny-redefine",
+        "--dialect",
+        "forth",
+        "VARIABLE len 5 len ! 2 len +! len ?",
+    ]);
+    assert_eq!("7 ", &res.stdout);
+    assert_eq!("", &res.stderr);
+}
+
+#[test]
+fn test_recurse() {
+    let res = forth(": fact DUP 1 > IF DUP 1- RECURSE * THEN ; 5 fact .");
+    assert_eq!("120 ", &res.stdout);
+}
+
+#[test]
+fn test_comments() {
+    assert_eq!(
+        "3 ",
+        &forth("1 ( a comment ) 2 + \\ to the end of the line\n .").stdout
+    );
+}
+
+#[test]
+fn test_unbalanced_control_words() {
+    let res = forth(": broken 1 IF 2 ;");
+    assert!(
+        res.stderr
+            .contains("The IF at line 1, column 12 is never closed"),
+        "{}",
+        res.stderr
+    );
+
+    let res = forth("1 THEN");
+    assert!(
+        res.stderr
+            .contains("The THEN at line 1, column 3 has no matching IF"),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_run_forth_file() {
+    let res = railsh_run_file("tests/forth/fizzbuzz.fth");
+    assert_eq!(
+        "1 2 Fizz 4 Buzz Fizz 7 8 Fizz Buzz 11 Fizz 13 14 FizzBuzz \n5 \n",
+        &res.stdout
+    );
+    assert_eq!("", &res.stderr);
+}
+
+#[test]
+fn test_dialect_flag_overrides_extension() {
+    let res = railsh_args(&["--dialect", "rail", "run", "tests/forth/fizzbuzz.fth"]);
+    assert!(!res.stderr.is_empty());
+}
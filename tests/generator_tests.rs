@@ -0,0 +1,154 @@
+mod rail_runner;
+
+use rail_runner::{rail_oneliner, rail_with_stdin};
+
+const FIBONACCI: &str = "[ 0 1 ] [ [ a b ] -> a yield b a b + ] generator";
+
+const COUNTDOWN: &str = "[ 3 ] [ [ [ dup 0 lt? ] [ dup yield 1 - ] ] ? ] generator";
+
+#[test]
+fn test_next_takes_values_from_an_infinite_generator() {
+    let res = rail_oneliner(&format!(
+        "{} next print next print next print next print next print next print drop",
+        FIBONACCI
+    ));
+    assert_eq!("011235", &res.stdout);
+}
+
+#[test]
+fn test_next_leaves_the_old_generator_as_it_was() {
+    let res = rail_oneliner(&format!(
+        "{} dup next print drop next print drop",
+        FIBONACCI
+    ));
+    assert_eq!("00", &res.stdout);
+}
+
+#[test]
+fn test_ended_generator_produces_none() {
+    let res = rail_oneliner("[ ] [ ] generator next print next print drop");
+    assert_eq!("nonenone", &res.stdout);
+}
+
+#[test]
+fn test_each_runs_until_the_generator_ends() {
+    let res = rail_oneliner(&format!("{} [ print ] each", COUNTDOWN));
+    assert_eq!("321", &res.stdout);
+}
+
+#[test]
+fn test_map_is_lazy_over_infinite_generators() {
+    let res = rail_oneliner(&format!(
+        "{} [ [ n ] -> [ [ n 2 mod 0 eq? ] [ n ] ] ? ] map next print next print next print drop",
+        FIBONACCI
+    ));
+    assert_eq!("028", &res.stdout);
+}
+
+#[test]
+fn test_map_then_each() {
+    let res = rail_oneliner(&format!("{} [ 10 * ] map [ print ] each!", COUNTDOWN));
+    assert_eq!("302010", &res.stdout);
+}
+
+#[test]
+fn test_step_can_yield_many_values() {
+    let res = rail_oneliner(
+        "[ 1 ] [ dup yield dup yield 1 + ] generator next print next print next print drop",
+    );
+    assert_eq!("112", &res.stdout);
+}
+
+#[test]
+fn test_yield_outside_a_generator() {
+    let res = rail_oneliner("1 yield");
+    assert!(
+        res.stderr.contains("Can't yield outside a generator"),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_generators_equal_only_their_copies() {
+    let res = rail_oneliner(&format!("{} dup eq? print", FIBONACCI));
+    assert_eq!("true", &res.stdout);
+
+    let res = rail_oneliner(&format!("{} {} eq? print", FIBONACCI, FIBONACCI));
+    assert_eq!("false", &res.stdout);
+}
+
+#[test]
+fn test_map_actions_yield_to_no_generator() {
+    let outer = "quote [ next 10 * yield ] generator 3 take collect print";
+    let res = rail_oneliner(&format!("{} [ 1 + ] map {}", FIBONACCI, outer));
+    assert_eq!("[ 10 20 20 ]", &res.stdout);
+
+    let res = rail_oneliner(&format!("{} [ dup yield ] map {}", FIBONACCI, outer));
+    assert!(
+        res.stderr.contains("Can't yield outside a generator"),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_generator_type_and_source() {
+    let res = rail_oneliner(&format!("{} type print", FIBONACCI));
+    assert_eq!("generator", &res.stdout);
+
+    let res = rail_oneliner(&format!("{} to-source eval next print drop", FIBONACCI));
+    assert_eq!("0", &res.stdout);
+}
+
+#[test]
+fn test_take_and_collect() {
+    let res = rail_oneliner(&format!("{} 8 take collect print", FIBONACCI));
+    assert_eq!("[ 0 1 1 2 3 5 8 13 ]", &res.stdout);
+
+    let res = rail_oneliner(&format!("{} 0 take collect print", FIBONACCI));
+    assert_eq!("[ ]", &res.stdout);
+}
+
+#[test]
+fn test_filter_is_lazy_over_infinite_generators() {
+    let res = rail_oneliner(&format!(
+        "{} [ 2 mod 0 eq? ] filter 4 take collect print",
+        FIBONACCI
+    ));
+    assert_eq!("[ 0 2 8 34 ]", &res.stdout);
+}
+
+#[test]
+fn test_lines_of() {
+    let res = rail_oneliner("\"tests/lines.txt\" lines-of collect print");
+    assert_eq!("[ \"first\" \"second\" \"third\" ]", &res.stdout);
+
+    let res = rail_oneliner("\"tests/lines.txt\" lines-of [ len ] map [ print ] each");
+    assert_eq!("565", &res.stdout);
+}
+
+#[test]
+fn test_lines_of_a_missing_file() {
+    let res = rail_oneliner("\"tests/no-such-file.txt\" lines-of");
+    assert!(
+        res.stderr.contains("Can't open tests/no-such-file.txt"),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_take_from_neither_quote_nor_generator() {
+    let res = rail_oneliner("\"abc\" 2 take");
+    assert!(res.stderr.contains("Type mismatch"), "{}", res.stderr);
+}
+
+#[test]
+fn test_stdin_lines_reads_only_what_it_needs() {
+    let res = rail_with_stdin(
+        &["stdin-lines 2 take [ print ] each stdin-lines collect print"],
+        "one\ntwo\nthree\n",
+    );
+    assert_eq!("onetwo[ \"three\" ]", &res.stdout);
+}
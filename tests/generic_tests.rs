@@ -0,0 +1,72 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_dispatch_on_top_type() {
+    let methods = "[ drop \"a string\" ] [ string ] \"describe-me\" defmethod [ drop \"a number\" ] [ num ] \"describe-me\" defmethod";
+    assert_eq!(
+        "a string",
+        &rail_oneliner(&format!("{} \"hi\" describe-me print", methods)).stdout
+    );
+    assert_eq!(
+        "a number",
+        &rail_oneliner(&format!("{} 2.5 describe-me print", methods)).stdout
+    );
+}
+
+#[test]
+fn test_dispatch_on_several_types() {
+    let methods = "[ + ] [ i64 i64 ] \"combine\" defmethod [ swap append ] [ string i64 ] \"combine\" defmethod";
+    assert_eq!(
+        "5",
+        &rail_oneliner(&format!("{} 2 3 combine print", methods)).stdout
+    );
+}
+
+#[test]
+fn test_later_method_replaces_same_signature() {
+    let methods = "[ drop 1 ] [ string ] \"m\" defmethod [ drop 2 ] [ string ] \"m\" defmethod";
+    assert_eq!(
+        "2",
+        &rail_oneliner(&format!("{} \"x\" m print", methods)).stdout
+    );
+}
+
+#[test]
+fn test_dispatch_on_user_types() {
+    let program = "[ x y ] \"point\" record [ point-x ] [ point ] \"len\" defmethod";
+    assert_eq!(
+        "7",
+        &rail_oneliner(&format!("{} 7 8 point len print", program)).stdout
+    );
+}
+
+#[test]
+fn test_methods_reject_unknown_types() {
+    let program = "[ x y ] \"point\" record [ drop 2 ] [ pont ] \"len\" defmethod";
+    let res = rail_oneliner(&format!("{} 7 8 point len print", program));
+    assert!(res.stderr.contains("len names pont, which is not a type"));
+    assert_ne!("2", &res.stdout);
+}
+
+#[test]
+fn test_extending_a_builtin_keeps_it_as_fallback() {
+    let program = "[ x y ] \"point\" record [ drop 2 ] [ point ] \"len\" defmethod";
+    assert_eq!(
+        "3",
+        &rail_oneliner(&format!("{} [ 1 2 3 ] len print", program)).stdout
+    );
+    assert_eq!(
+        "2",
+        &rail_oneliner(&format!("{} 7 8 point len print", program)).stdout
+    );
+}
+
+#[test]
+fn test_no_matching_method() {
+    let res =
+        rail_oneliner("[ drop \"a string\" ] [ string ] \"describe-me\" defmethod 5 describe-me");
+    assert!(res.stderr.contains("Type mismatch"));
+    assert!(res.stderr.contains("String"));
+}
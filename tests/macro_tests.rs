@@ -0,0 +1,84 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+const FN_MACRO: &str = "[ deq swap quote swap [ rest ] => \
+    rest [ ; ] deq drop index-of [ n ] => \
+    rest n take swap def \
+    rest n 1 + drop-first ] [ fn ] defmacro";
+
+#[test]
+fn test_macro_rewrites_the_quote_under_construction() {
+    let res =
+        rail_oneliner("[ deq swap 2 * swap ] [ twice-next ] defmacro [ twice-next 21 1 ] print");
+    assert_eq!("[ 42 1 ]", &res.stdout);
+}
+
+#[test]
+fn test_macro_defines_words() {
+    let res = rail_oneliner(&format!("{} fn square dup * ; 3 square print", FN_MACRO));
+    assert_eq!("9", &res.stdout);
+}
+
+#[test]
+fn test_macro_inside_quote() {
+    let res = rail_oneliner(&format!(
+        "{} [ fn cube dup dup * * ; 2 cube ] do print",
+        FN_MACRO
+    ));
+    assert_eq!("8", &res.stdout);
+}
+
+#[test]
+fn test_macro_output_expands_again() {
+    let res = rail_oneliner(
+        "[ deq swap 2 * swap ] [ twice-next ] defmacro \
+         [ deq deq swap quote rot swap quote swap concat concat ] [ swap-next ] defmacro \
+         swap-next 5 twice-next print",
+    );
+    assert_eq!("10", &res.stdout);
+}
+
+#[test]
+fn test_comments_survive_expansion() {
+    let res = rail_oneliner(
+        "[ ] [ nothing ] defmacro nothing\n#: Pushes one.\n[ 1 ] [ one ] def! \"one\" describe print",
+    );
+    assert_eq!("Pushes one.", &res.stdout);
+
+    let res = rail_oneliner("[ ] [ nothing ] defmacro nothing ( -- n ) [ 1 ( -- n ) ] do print");
+    assert_eq!("1", &res.stdout);
+    assert_eq!("", &res.stderr);
+}
+
+#[test]
+fn test_macros_see_comments_as_comments() {
+    let res =
+        rail_oneliner("[ dup to-source print ] [ show ] defmacro show\n#: Doc.\n( a -- ) 1 drop");
+    assert_eq!("[ #: Doc.\n ( a -- ) 1 drop ]", &res.stdout);
+    assert_eq!("", &res.stderr);
+}
+
+#[test]
+fn test_immediate_existing_command() {
+    let res = rail_oneliner(
+        "[ [ \"hi\" print ] swap concat ] [ hi-first ] def! \"hi-first\" immediate hi-first 1 print",
+    );
+    assert_eq!("hi1", &res.stdout);
+}
+
+#[test]
+fn test_see_macro() {
+    let res = rail_oneliner("[ ] [ nothing ] defmacro \"nothing\" see print");
+    assert_eq!(
+        "# nothing from user\n[ ] [ nothing ] def!\n\"nothing\" immediate",
+        &res.stdout
+    );
+}
+
+#[test]
+fn test_macro_must_produce_a_quote() {
+    let res = rail_oneliner("[ drop 1 ] [ bad ] defmacro bad 2 print");
+    assert_eq!("", &res.stdout);
+    assert!(res.stderr.contains("Type mismatch"), "{}", res.stderr);
+}
@@ -0,0 +1,90 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_pow() {
+    assert_eq!("1024", &rail_oneliner("2 10 pow print").stdout);
+    assert_eq!("0.25", &rail_oneliner("2 -2 pow print").stdout);
+    assert_eq!("3", &rail_oneliner("9 0.5 pow print").stdout);
+    assert_eq!(
+        "18446744073709552000",
+        &rail_oneliner("2 64 pow print").stdout
+    );
+}
+
+#[test]
+fn test_logs() {
+    assert_eq!("1", &rail_oneliner("e ln print").stdout);
+    assert_eq!("3", &rail_oneliner("8 2 log print").stdout);
+    assert_eq!("1", &rail_oneliner("0 exp print").stdout);
+}
+
+#[test]
+fn test_trig() {
+    assert_eq!("0", &rail_oneliner("0 sin print").stdout);
+    assert_eq!("-1", &rail_oneliner("pi cos print").stdout);
+    assert_eq!("true", &rail_oneliner("1 1 atan2 pi 4 / eq? print").stdout);
+}
+
+#[test]
+fn test_rounding() {
+    assert_eq!("-3", &rail_oneliner("2.5 negate floor print").stdout);
+    assert_eq!("-2", &rail_oneliner("2.5 negate ceil print").stdout);
+    assert_eq!("-3", &rail_oneliner("2.5 negate round print").stdout);
+    assert_eq!("-2", &rail_oneliner("2.5 negate trunc print").stdout);
+    assert_eq!("7", &rail_oneliner("7 round print").stdout);
+}
+
+#[test]
+fn test_gcd_lcm() {
+    assert_eq!("6", &rail_oneliner("12 18 gcd print").stdout);
+    assert_eq!("36", &rail_oneliner("12 18 lcm print").stdout);
+    assert_eq!("0", &rail_oneliner("0 18 lcm print").stdout);
+    assert_eq!("2", &rail_oneliner("int-min 2 gcd print").stdout);
+}
+
+#[test]
+fn test_gcd_lcm_overflow() {
+    let res = rail_oneliner("0 int-min gcd");
+    assert!(res.stderr.contains("Integer overflow"), "{}", res.stderr);
+
+    let res = rail_oneliner("int-max 2 lcm");
+    assert!(res.stderr.contains("Integer overflow"), "{}", res.stderr);
+}
+
+#[test]
+fn test_min_max() {
+    assert_eq!("2", &rail_oneliner("2 3 min print").stdout);
+    assert_eq!("3.5", &rail_oneliner("2 3.5 max print").stdout);
+    assert_eq!("3", &rail_oneliner("3 2.5 max print").stdout);
+    assert_eq!("2", &rail_oneliner("2 2.5 min print").stdout);
+    assert_eq!("9", &rail_oneliner("[ 4 9 2 ] maximum print").stdout);
+}
+
+#[test]
+fn test_bitwise() {
+    assert_eq!("8", &rail_oneliner("12 10 bit-and print").stdout);
+    assert_eq!("14", &rail_oneliner("12 10 bit-or print").stdout);
+    assert_eq!("6", &rail_oneliner("12 10 bit-xor print").stdout);
+    assert_eq!("40", &rail_oneliner("5 3 shl print").stdout);
+    assert_eq!("-3", &rail_oneliner("12 negate 2 shr print").stdout);
+}
+
+#[test]
+fn test_numeric_literals() {
+    let res = rail_oneliner("0xff 0o17 0b1010 1_000_000 1e9i64 2f64 status");
+    assert_eq!("[ 255 15 10 1000000 1000000000 2 ]\n", res.stdout);
+}
+
+#[test]
+fn test_character_literals() {
+    let res = rail_oneliner("'a' 'z' '\\n' status");
+    assert_eq!("[ 97 122 10 ]\n", res.stdout);
+}
+
+#[test]
+fn test_suffixes_pick_the_type() {
+    let res = rail_oneliner("2f64 type print 2.0i64 type print");
+    assert_eq!("f64i64", res.stdout);
+}
@@ -0,0 +1,42 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_none_predicates() {
+    assert_eq!("none", &rail_oneliner("none print").stdout);
+    assert_eq!("none", &rail_oneliner("none type print").stdout);
+    assert_eq!("true", &rail_oneliner("none none? print").stdout);
+    assert_eq!("false", &rail_oneliner("1 none? print").stdout);
+    assert_eq!("true", &rail_oneliner("1 some? print").stdout);
+    assert_eq!("false", &rail_oneliner("none some? print").stdout);
+    assert_eq!("true", &rail_oneliner("none none eq? print").stdout);
+}
+
+#[test]
+fn test_or_default() {
+    assert_eq!("1", &rail_oneliner("1 2 or-default print").stdout);
+    assert_eq!("2", &rail_oneliner("none 2 or-default print").stdout);
+    assert_eq!(
+        "0",
+        &rail_oneliner("[ 1 2 ] 7 nth? 0 or-default print").stdout
+    );
+}
+
+#[test]
+fn test_maybe() {
+    assert_eq!("4", &rail_oneliner("2 [ dup * ] maybe print").stdout);
+    assert_eq!("none", &rail_oneliner("none [ dup * ] maybe print").stdout);
+}
+
+#[test]
+fn test_envget() {
+    assert_eq!(
+        "none",
+        &rail_oneliner(r#""RAIL_SURELY_UNSET_VARIABLE" envget print"#).stdout
+    );
+    assert_eq!(
+        "yes",
+        &rail_oneliner(r#""RAIL_TEST_VAR" "yes" envset "RAIL_TEST_VAR" envget print"#).stdout
+    );
+}
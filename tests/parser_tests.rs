@@ -0,0 +1,59 @@
+mod rail_runner;
+
+use rail_runner::{rail_oneliner, railsh};
+
+#[test]
+fn test_unopened_bracket_before_running() {
+    let res = rail_oneliner("\"hi\" print ]");
+    assert_eq!("", &res.stdout);
+    assert!(
+        res.stderr
+            .contains("Unbalanced brackets. The ] at line 1, column 12 has no matching ["),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_unclosed_bracket_before_running() {
+    let res = rail_oneliner("\"hi\" print [ 1 [ 2 ]\n3");
+    assert_eq!("", &res.stdout);
+    assert!(
+        res.stderr
+            .contains("Unbalanced brackets. The [ at line 1, column 12 is never closed"),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_nested_quotes() {
+    let res = rail_oneliner("[ 1 [ 2 [ 3 ] ] [ ] ] print");
+    assert_eq!("[ 1 [ 2 [ 3 ] ] [ ] ]", &res.stdout);
+}
+
+#[test]
+fn test_repl_continues_open_quotes() {
+    let res = railsh("[ 1\n2 ] do + pl\n");
+    assert_eq!("3\n", &res.stdout);
+}
+
+#[test]
+fn test_repl_continues_open_strings() {
+    let res = railsh("\"one\ntwo\" pl\n");
+    assert_eq!("one\ntwo\n", &res.stdout);
+}
+
+#[test]
+fn test_repl_reports_unopened_brackets() {
+    let res = railsh("1 ] pl\n2 pl\n");
+    assert_eq!("2\n", &res.stdout);
+    assert!(res.stderr.contains("has no matching ["), "{}", res.stderr);
+}
+
+#[test]
+fn test_repl_reports_invalid_strings_without_waiting() {
+    let res = railsh("\"a\\qb\" pl\n2 pl\n");
+    assert_eq!("2\n", &res.stdout);
+    assert!(res.stderr.contains("Unknown escape"), "{}", res.stderr);
+}
@@ -0,0 +1,70 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_flat_bindings_still_work() {
+    assert_eq!("3", &rail_oneliner("1 2 [ a b ] -> a b + print").stdout);
+    assert_eq!(
+        "[ 1 2 ]",
+        &rail_oneliner("[ 1 2 ] [ xs ] => xs print").stdout
+    );
+}
+
+#[test]
+fn test_destructure_nested_quote() {
+    assert_eq!(
+        "v",
+        &rail_oneliner("[ \"k\" \"v\" ] 5 [ [ k v ] rest ] -> v print").stdout
+    );
+    assert_eq!(
+        "5",
+        &rail_oneliner("[ \"k\" \"v\" ] 5 [ [ k v ] rest ] -> rest print").stdout
+    );
+}
+
+#[test]
+fn test_destructure_stab_by_key() {
+    let program =
+        "stab [ \"name\" \"rail\" ] insert [ \"age\" 5 ] insert [ [ \"age\" a ] ] -> a print";
+    assert_eq!("5", &rail_oneliner(program).stdout);
+}
+
+#[test]
+fn test_destructure_record() {
+    let program = "[ x y ] \"point\" record 3 4 point [ [ point x y ] ] -> x y * print";
+    assert_eq!("12", &rail_oneliner(program).stdout);
+}
+
+#[test]
+fn test_wildcard() {
+    assert_eq!(
+        "3",
+        &rail_oneliner("[ 1 2 3 ] [ [ _ _ z ] ] -> z print").stdout
+    );
+}
+
+#[test]
+fn test_literal_mismatch_is_an_error() {
+    let res = rail_oneliner("[ 1 2 ] [ [ 0 b ] ] ->");
+    assert!(res.stderr.contains("Pattern mismatch"));
+    let res = rail_oneliner("[ 1 2 3 ] [ [ a b ] ] ->");
+    assert!(res.stderr.contains("Pattern mismatch"));
+}
+
+#[test]
+fn test_match_with_destructuring_clauses() {
+    let classify = "[ [ 0 ] [ \"zero\" ] [ [ a b ] ] [ a b + ] [ _ ] [ drop \"other\" ] ] match";
+    assert_eq!(
+        "zero",
+        &rail_oneliner(&format!("0 {} print", classify)).stdout
+    );
+    assert_eq!(
+        "7",
+        &rail_oneliner(&format!("[ 3 4 ] {} print", classify)).stdout
+    );
+    assert_eq!(
+        "other",
+        &rail_oneliner(&format!("\"x\" {} print", classify)).stdout
+    );
+}
@@ -1,4 +1,4 @@
-use std::io::{Read, Write};
+use std::io::Write;
 use std::process::{Command, ExitStatus, Output, Stdio};
 
 const RAIL_PATH: &str = std::env!("CARGO_BIN_EXE_rail");
@@ -37,7 +37,7 @@ impl From<Output> for RailRunResult {
 
 #[allow(dead_code)]
 pub fn railsh(stdin: &str) -> RailPipedResult {
-    let rail_proc = Command::new(RAILSH_PATH)
+    let mut rail_proc = Command::new(RAILSH_PATH)
         .args(DEV_MODE_ARGS)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -47,23 +47,15 @@ pub fn railsh(stdin: &str) -> RailPipedResult {
 
     rail_proc
         .stdin
+        .take()
         .expect("Error sending stdin")
         .write_all(stdin.as_bytes())
         .unwrap();
 
-    let mut stdout = String::new();
-    rail_proc
-        .stdout
-        .unwrap()
-        .read_to_string(&mut stdout)
-        .unwrap();
-
-    let mut stderr = String::new();
-    rail_proc
-        .stderr
-        .unwrap()
-        .read_to_string(&mut stderr)
-        .unwrap();
+    let RailRunResult { stdout, stderr, .. } = rail_proc
+        .wait_with_output()
+        .expect("Error running process")
+        .into();
 
     RailPipedResult { stdout, stderr }
 }
@@ -72,7 +64,7 @@ pub fn railsh(stdin: &str) -> RailPipedResult {
 pub fn railsh_run_file(file: &str) -> RailRunResult {
     Command::new(RAILSH_PATH)
         .args(DEV_MODE_ARGS)
-        .args(&["run", file])
+        .args(["run", file])
         .output()
         .expect("Error running process")
         .into()
@@ -92,3 +84,47 @@ pub fn rail(args: &[&str]) -> RailRunResult {
         .expect("Error running process")
         .into()
 }
+
+#[allow(dead_code)]
+pub fn railsh_args(args: &[&str]) -> RailRunResult {
+    Command::new(RAILSH_PATH)
+        .args(DEV_MODE_ARGS)
+        .args(args)
+        .output()
+        .expect("Error running process")
+        .into()
+}
+
+#[allow(dead_code)]
+pub fn rail_with_lib_list(lib_list: &str, args: &[&str]) -> RailRunResult {
+    Command::new(RAIL_PATH)
+        .args(["--no-stdlib", "--lib-list", lib_list])
+        .args(args)
+        .output()
+        .expect("Error running process")
+        .into()
+}
+
+#[allow(dead_code)]
+pub fn rail_with_stdin(args: &[&str], stdin: &str) -> RailRunResult {
+    let mut rail_proc = Command::new(RAIL_PATH)
+        .args(DEV_MODE_ARGS)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Error running process");
+
+    rail_proc
+        .stdin
+        .take()
+        .expect("Error sending stdin")
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    rail_proc
+        .wait_with_output()
+        .expect("Error running process")
+        .into()
+}
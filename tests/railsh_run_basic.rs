@@ -1,5 +1,5 @@
 mod rail_runner;
-use rail_runner::railsh_run_file;
+use rail_runner::{rail_with_lib_list, railsh_run_file};
 
 #[test]
 pub fn say_hello() {
@@ -9,3 +9,21 @@ pub fn say_hello() {
     assert_eq!("Hello world!\n", res.stdout);
     assert_eq!("", res.stderr);
 }
+
+#[test]
+pub fn missing_file_is_an_error() {
+    let res = railsh_run_file("tests/missing.rail");
+
+    assert!(!res.status.success());
+    assert!(res.stderr.contains("Can't open tests/missing.rail"));
+    assert!(!res.stderr.contains("panicked"));
+}
+
+#[test]
+pub fn broken_library_is_an_error() {
+    let res = rail_with_lib_list("tests/broken-lib/all.txt", &["1 print"]);
+
+    assert!(!res.status.success());
+    assert!(res.stderr.contains("Error loading initial state"));
+    assert!(!res.stderr.contains("panicked"));
+}
@@ -0,0 +1,53 @@
+mod rail_runner;
+
+use rail_runner::{rail, rail_oneliner};
+
+#[test]
+fn seed_flag_is_reproducible() {
+    let source = "rand-i64 print rand-f64 print [ 1 2 3 4 5 ] shuffle print";
+
+    let first = rail(&["--seed", "42", source]);
+    let second = rail(&["--seed", "42", source]);
+
+    assert_eq!("", first.stderr);
+    assert_eq!(first.stdout, second.stdout);
+}
+
+#[test]
+fn seed_word_is_reproducible() {
+    let source = r#"
+        7 seed rand-i64 [ 1 2 3 ] choice 10 20 rand-range
+        7 seed rand-i64 [ 1 2 3 ] choice 10 20 rand-range
+        [ a b c d e f ] ->
+        a d eq? b e eq? c f eq? and and print
+    "#;
+
+    assert_eq!("true", &rail_oneliner(source).stdout);
+}
+
+#[test]
+fn rand_range_is_in_bounds() {
+    let source = r#"
+        [ 1 2 3 4 5 6 7 8 9 10 ] [ drop -3 3 rand-range ] map
+        [ [ n ] -> -3 n gte? 3 n lt? and ] filter len print
+    "#;
+
+    assert_eq!("10", &rail(&["--seed", "1", source]).stdout);
+}
+
+#[test]
+fn rand_f64_is_in_unit_interval() {
+    let source = r#"
+        [ 1 2 3 4 5 6 7 8 9 10 ] [ drop rand-f64 ] map
+        [ [ n ] -> 0 n gte? 1 n lt? and ] filter len print
+    "#;
+
+    assert_eq!("10", &rail(&["--seed", "1", source]).stdout);
+}
+
+#[test]
+fn shuffle_keeps_values() {
+    let source = "[ 1 2 3 4 5 ] shuffle dup len print [ [ + ] doin ] 4 times unquote print";
+
+    assert_eq!("515", &rail(&["--seed", "3", source]).stdout);
+}
@@ -0,0 +1,150 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_record_constructor_and_accessors() {
+    assert_eq!(
+        "1",
+        &rail_oneliner("[ x y ] \"point\" record 1 2 point point-x print").stdout
+    );
+    assert_eq!(
+        "2",
+        &rail_oneliner("[ x y ] \"point\" record 1 2 point point-y print").stdout
+    );
+    assert_eq!(
+        "point { x: 1 y: 2 }",
+        &rail_oneliner("[ x y ] \"point\" record 1 2 point print").stdout
+    );
+}
+
+#[test]
+fn test_record_type_and_predicate() {
+    assert_eq!(
+        "point",
+        &rail_oneliner("[ x y ] \"point\" record 1 2 point type print").stdout
+    );
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ x y ] \"point\" record 1 2 point point? print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ x y ] \"point\" record 5 point? print").stdout
+    );
+}
+
+#[test]
+fn test_variants() {
+    let program = "[ [ circle r ] [ rect w h ] ] \"shape\" variants 2 3 rect";
+    assert_eq!(
+        "shape",
+        &rail_oneliner(&format!("{} type print", program)).stdout
+    );
+    assert_eq!(
+        "true",
+        &rail_oneliner(&format!("{} rect? print", program)).stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner(&format!("{} circle? print", program)).stdout
+    );
+    assert_eq!(
+        "true",
+        &rail_oneliner(&format!("{} shape? print", program)).stdout
+    );
+    assert_eq!(
+        "3",
+        &rail_oneliner(&format!("{} rect-h print", program)).stdout
+    );
+}
+
+#[test]
+fn test_match_on_variant() {
+    let shapes = "[ [ circle r ] [ rect w h ] ] \"shape\" variants";
+    let area = "[ [ circle ] [ dup * 3 * ] [ rect ] [ * ] ] match";
+    assert_eq!(
+        "6",
+        &rail_oneliner(&format!("{} 2 3 rect {} print", shapes, area)).stdout
+    );
+    assert_eq!(
+        "12",
+        &rail_oneliner(&format!("{} 2 circle {} print", shapes, area)).stdout
+    );
+}
+
+#[test]
+fn test_match_wildcard_and_type_name() {
+    assert_eq!(
+        "other",
+        &rail_oneliner("5 [ [ string ] [ ] [ _ ] [ drop \"other\" ] ] match print").stdout
+    );
+    assert_eq!(
+        "hi",
+        &rail_oneliner("\"hi\" [ [ string ] [ ] [ _ ] [ drop \"other\" ] ] match print").stdout
+    );
+}
+
+#[test]
+fn test_match_binds_names_that_are_not_types() {
+    assert_eq!(
+        "6",
+        &rail_oneliner("5 [ [ n ] [ n 1 + ] ] match print").stdout
+    );
+    assert_eq!(
+        "3",
+        &rail_oneliner("[ 1 2 ] [ [ a b ] [ a b + ] ] match print").stdout
+    );
+}
+
+#[test]
+fn test_match_strings_by_value() {
+    let cases = "[ \"hi\" [ 1 ] _ [ drop 2 ] ] match print";
+    assert_eq!("1", &rail_oneliner(&format!("\"hi\" {}", cases)).stdout);
+    assert_eq!("2", &rail_oneliner(&format!("\"string\" {}", cases)).stdout);
+}
+
+#[test]
+fn test_match_on_declared_type() {
+    let shapes = "[ [ circle r ] [ rect w h ] ] \"shape\" variants";
+    let cases = "[ [ shape ] [ drop \"a shape\" ] _ [ drop \"other\" ] ] match print";
+    assert_eq!(
+        "a shape",
+        &rail_oneliner(&format!("{} 2 circle {}", shapes, cases)).stdout
+    );
+    assert_eq!(
+        "other",
+        &rail_oneliner(&format!("{} 2 {}", shapes, cases)).stdout
+    );
+}
+
+#[test]
+fn test_match_without_match_is_an_error() {
+    let res = rail_oneliner("5 [ [ string ] [ ] ] match");
+    assert!(res.stderr.contains("Pattern mismatch"));
+}
+
+#[test]
+fn test_accessor_on_wrong_variant_is_an_error() {
+    let res = rail_oneliner("[ [ circle r ] [ rect w h ] ] \"shape\" variants 2 circle rect-w");
+    assert!(res.stderr.contains("Type mismatch"));
+}
+
+#[test]
+fn test_variants_of_other_types_do_not_match() {
+    let program = "[ [ leaf v ] ] \"a\" variants 1 leaf [ [ leaf v ] ] \"b\" variants";
+    assert_eq!(
+        "false",
+        &rail_oneliner(&format!("{} leaf? print", program)).stdout
+    );
+    assert_eq!(
+        "other",
+        &rail_oneliner(&format!(
+            "{} [ [ leaf ] [ \"b\" ] _ [ drop \"other\" ] ] match print",
+            program
+        ))
+        .stdout
+    );
+    let res = rail_oneliner(&format!("{} leaf-v", program));
+    assert!(res.stderr.contains("Type mismatch"));
+}
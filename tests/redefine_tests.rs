@@ -0,0 +1,113 @@
+mod rail_runner;
+
+use rail_runner::{rail, rail_oneliner};
+
+#[test]
+fn test_forget_removes_a_definition() {
+    let res = rail_oneliner("[ 1 ] [ one ] def [ one ] forget [ one ] def? print");
+    assert_eq!("false", &res.stdout);
+}
+
+#[test]
+fn test_forget_restores_a_shadowed_definition() {
+    let res = rail_oneliner("[ 1 ] [ n ] def [ 2 ] [ n ] def [ n ] forget n print");
+    assert_eq!("1", &res.stdout);
+}
+
+#[test]
+fn test_forget_restores_a_builtin() {
+    let res = rail_oneliner("[ * ] [ + ] def [ + ] forget 2 3 + print");
+    assert_eq!("5", &res.stdout);
+}
+
+#[test]
+fn test_forget_restores_what_a_binding_shadowed() {
+    let res = rail_oneliner("3 [ + ] -> + print [ + ] forget 2 3 + print");
+    assert_eq!("35", &res.stdout);
+}
+
+#[test]
+fn test_forget_after_doc_removes_the_definition() {
+    let res = rail_oneliner(
+        "[ 1 ] [ one ] def [ one ] \"Pushes one.\" doc [ one ] forget [ one ] def? print",
+    );
+    assert_eq!("false", &res.stdout);
+}
+
+#[test]
+fn test_rebinding_replaces_the_binding() {
+    let res = rail_oneliner("1 [ x ] -> 2 [ x ] -> [ x ] forget [ x ] def? print");
+    assert_eq!("false", &res.stdout);
+}
+
+#[test]
+fn test_overriding_a_builtin_warns() {
+    let res = rail_oneliner("[ * ] [ + ] def 2 3 + print");
+    assert_eq!("6", &res.stdout);
+    assert_eq!(
+        "[Warn] + overrides the definition from corelib/math\n",
+        &res.stderr
+    );
+}
+
+#[test]
+fn test_overriding_a_stdlib_word_warns() {
+    let res = rail_oneliner("[ 1 ] [ def ] def!");
+    assert_eq!(
+        "[Warn] def overrides the definition from stdlib/command.rail:1\n",
+        &res.stderr
+    );
+}
+
+#[test]
+fn test_redefining_a_user_word_is_quiet() {
+    let res = rail_oneliner("[ 1 ] [ n ] def [ 2 ] [ n ] def n print");
+    assert_eq!("2", &res.stdout);
+    assert_eq!("", &res.stderr);
+}
+
+#[test]
+fn test_deny_redefine() {
+    let res = rail(&["--deny-redefine", "[ * ] [ + ] def 2 3 + print"]);
+    assert_eq!("", &res.stdout);
+    assert!(res
+        .stderr
+        .contains("Redefinition. + would override the definition from corelib/math"));
+}
+
+#[test]
+fn test_deny_redefine_covers_every_way_of_defining() {
+    let programs = [
+        "[ dup ] [ len ] alias",
+        "[ x ] \"len\" record",
+        "[ [ len ] [ other ] ] \"shape\" variants",
+        "[ len ] \"Counts.\" doc",
+        "\"len\" immediate",
+    ];
+    for program in programs {
+        let res = rail(&["--deny-redefine", &format!("{} \"ok\" print", program)]);
+        assert_eq!("", &res.stdout, "{}", program);
+        assert!(
+            res.stderr
+                .contains("Redefinition. len would override the definition from corelib/"),
+            "{}: {}",
+            program,
+            res.stderr
+        );
+    }
+}
+
+#[test]
+fn test_bindings_and_methods_do_not_count_as_redefining() {
+    let programs = [
+        "5 [ len ] -> len print",
+        "5 [ len ] => len print",
+        "5 [ [ len ] [ len print ] ] match",
+        "[ drop 5 ] [ i64 ] [ len ] defmethod 1 len print",
+    ];
+    for program in programs {
+        let res = rail(&["--deny-redefine", program]);
+        assert_eq!("5", &res.stdout, "{}", program);
+        assert_eq!("", &res.stderr, "{}", program);
+    }
+}
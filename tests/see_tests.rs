@@ -0,0 +1,55 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_see_user_definition() {
+    assert_eq!(
+        "# double from user\n[ 2 * ] [ double ] def",
+        &rail_oneliner("[ 2 * ] [ double ] def [ double ] see print").stdout
+    );
+}
+
+#[test]
+fn test_see_stdlib_definition() {
+    assert_eq!(
+        "# def from stdlib/command.rail:1\n[ swap quote [ do ] concat swap def! ] [ def ] def!",
+        &rail_oneliner("[ def ] see print").stdout
+    );
+}
+
+#[test]
+fn test_see_typed_definition() {
+    assert_eq!(
+        "# inc from user\n[ 1 + ] [ inc ] [ i64 -- i64 ] def-typed",
+        &rail_oneliner("[ 1 + ] [ inc ] [ i64 -- i64 ] def-typed [ inc ] see print").stdout
+    );
+}
+
+#[test]
+fn test_see_builtin() {
+    assert_eq!(
+        "# sqrt from corelib/math\nbuiltin (Rust) [ num -- f64 ]",
+        &rail_oneliner("[ sqrt ] see print").stdout
+    );
+}
+
+#[test]
+fn test_see_generic() {
+    let res = rail_oneliner(
+        "[ drop 1 ] [ string ] \"m\" defmethod [ drop 2 ] [ i64 ] \"m\" defmethod [ m ] see print",
+    );
+    assert_eq!(
+        "# m from user\n[ drop 1 ] [ string ] \"m\" defmethod\n[ drop 2 ] [ i64 ] \"m\" defmethod",
+        &res.stdout
+    );
+}
+
+#[test]
+fn test_see_produces_a_string() {
+    assert_eq!("string", &rail_oneliner("[ sqrt ] see type print").stdout);
+    assert_eq!(
+        "Command \"nope\" is unknown.",
+        &rail_oneliner("[ nope ] see print").stdout
+    );
+}
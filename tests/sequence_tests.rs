@@ -0,0 +1,148 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_fold_reduce_scan() {
+    assert_eq!(
+        "10",
+        &rail_oneliner("[ 1 2 3 4 ] 0 [ + ] fold print").stdout
+    );
+    assert_eq!(
+        "24",
+        &rail_oneliner("[ 1 2 3 4 ] [ * ] reduce print").stdout
+    );
+    assert_eq!(
+        "[ 1 3 6 10 ]",
+        &rail_oneliner("[ 1 2 3 4 ] 0 [ + ] scan print").stdout
+    );
+}
+
+#[test]
+fn test_flat_map_and_flatten() {
+    assert_eq!(
+        "[ 1 1 2 2 ]",
+        &rail_oneliner("[ 1 2 ] [ dup quote swap push ] flat-map print").stdout
+    );
+    assert_eq!(
+        "[ 1 2 3 4 ]",
+        &rail_oneliner("[ [ 1 2 ] 3 [ 4 ] ] flatten print").stdout
+    );
+}
+
+#[test]
+fn test_take_drop_slice() {
+    assert_eq!("[ 1 2 ]", &rail_oneliner("[ 1 2 3 ] 2 take print").stdout);
+    assert_eq!(
+        "[ 3 ]",
+        &rail_oneliner("[ 1 2 3 ] 2 drop-first print").stdout
+    );
+    assert_eq!("[ 1 2 3 ]", &rail_oneliner("[ 1 2 3 ] 9 take print").stdout);
+    assert_eq!(
+        "[ 2 3 ]",
+        &rail_oneliner("[ 1 2 3 4 ] 1 3 slice print").stdout
+    );
+}
+
+#[test]
+fn test_while_predicates() {
+    assert_eq!(
+        "[ 2 4 ]",
+        &rail_oneliner("[ 2 4 5 6 ] [ even? ] take-while print").stdout
+    );
+    assert_eq!(
+        "[ 5 6 ]",
+        &rail_oneliner("[ 2 4 5 6 ] [ even? ] drop-while print").stdout
+    );
+}
+
+#[test]
+fn test_searching() {
+    assert_eq!("5", &rail_oneliner("[ 2 5 7 ] [ odd? ] find print").stdout);
+    assert_eq!("none", &rail_oneliner("[ 2 4 ] [ odd? ] find print").stdout);
+    assert_eq!("1", &rail_oneliner("[ 2 5 7 ] 5 index-of print").stdout);
+    assert_eq!("-1", &rail_oneliner("[ 2 5 7 ] 3 index-of print").stdout);
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ 2 4 ] [ even? ] all? print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ 2 5 ] [ even? ] all? print").stdout
+    );
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ 1 2 ] [ even? ] any? print").stdout
+    );
+    assert_eq!("false", &rail_oneliner("[ ] [ even? ] any? print").stdout);
+    assert_eq!("true", &rail_oneliner("[ 1 2 ] [ even? ] any print").stdout);
+}
+
+#[test]
+fn test_grouping() {
+    assert_eq!(
+        "[ 1 3 ]",
+        &rail_oneliner(r#"[ 1 2 3 4 ] [ even? ] group-by "false" extract print drop"#).stdout
+    );
+    assert_eq!(
+        "[ 2 4 ][ 1 3 ]",
+        &rail_oneliner("[ 1 2 3 4 ] [ even? ] partition swap print print").stdout
+    );
+    assert_eq!(
+        "[ [ 1 2 ] [ 3 4 ] [ 5 ] ]",
+        &rail_oneliner("[ 1 2 3 4 5 ] 2 chunks print").stdout
+    );
+    assert_eq!(
+        "[ [ 1 2 ] [ 2 3 ] ]",
+        &rail_oneliner("[ 1 2 3 ] 2 windows print").stdout
+    );
+}
+
+#[test]
+fn test_range() {
+    assert_eq!("[ 1 2 3 ]", &rail_oneliner("1 3 1 range print").stdout);
+    assert_eq!("[ 0 3 6 9 ]", &rail_oneliner("0 10 3 range print").stdout);
+    assert_eq!("[ 3 2 1 ]", &rail_oneliner("3 1 -1 range print").stdout);
+    assert_eq!("[ 3 ]", &rail_oneliner("3 1 1 range print").stdout);
+    assert_eq!("[ 0 ]", &rail_oneliner("0 -5 2 range print").stdout);
+}
+
+#[test]
+fn test_nth_out_of_bounds() {
+    let res = rail_oneliner("[ 1 2 ] 5 nth");
+    assert!(res
+        .stderr
+        .contains("Index out of bounds. nth wanted index 5 but the quote had 2 elements"));
+
+    assert_eq!("2", &rail_oneliner("[ 1 2 ] 1 nth? print").stdout);
+    assert_eq!("none", &rail_oneliner("[ 1 2 ] 5 nth? print").stdout);
+    assert_eq!("none", &rail_oneliner("[ 1 2 ] -1 nth? print").stdout);
+}
+
+#[test]
+fn test_empty_collections() {
+    assert!(rail_oneliner("[ ] pop")
+        .stderr
+        .contains("Empty collection. pop wanted at least one element"));
+    assert!(rail_oneliner("[ ] deq")
+        .stderr
+        .contains("Empty collection. deq wanted at least one element"));
+    assert!(rail_oneliner("[ ] [ + ] reduce")
+        .stderr
+        .contains("Empty collection. reduce wanted at least one element"));
+}
+
+#[test]
+fn test_missing_key() {
+    let res = rail_oneliner(r#"stab "a" extract"#);
+    assert!(res.stderr.contains("Missing key. extract wanted \"a\""));
+
+    assert_eq!(
+        "none",
+        &rail_oneliner(r#"stab "a" extract? print drop"#).stdout
+    );
+    assert_eq!(
+        "1",
+        &rail_oneliner(r#"stab [ "a" 1 ] insert "a" extract? print drop"#).stdout
+    );
+}
@@ -0,0 +1,105 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_set_dedupes() {
+    assert_eq!(
+        "[ 1 2 3 ]",
+        &rail_oneliner("[ 2 1 3 2 1 ] set members print").stdout
+    );
+    assert_eq!(
+        "3",
+        &rail_oneliner("[ 2 1 3 2 1 ] set members len print").stdout
+    );
+}
+
+#[test]
+fn test_add_remove_member() {
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ ] set 5 add 5 member? print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ 5 ] set 5 remove 5 member? print").stdout
+    );
+    assert_eq!(
+        "[ \"a\" ]",
+        &rail_oneliner("[ \"a\" \"b\" ] set \"b\" remove members print").stdout
+    );
+}
+
+#[test]
+fn test_remove_still_works_on_stabs() {
+    assert_eq!(
+        "false",
+        &rail_oneliner("stab [ \"a\" 1 ] insert \"a\" remove \"a\" has-key? print").stdout
+    );
+}
+
+#[test]
+fn test_set_algebra() {
+    assert_eq!(
+        "[ 1 2 3 4 ]",
+        &rail_oneliner("[ 1 2 3 ] set [ 2 3 4 ] set union members print").stdout
+    );
+    assert_eq!(
+        "[ 2 3 ]",
+        &rail_oneliner("[ 1 2 3 ] set [ 2 3 4 ] set intersection members print").stdout
+    );
+    assert_eq!(
+        "[ 1 ]",
+        &rail_oneliner("[ 1 2 3 ] set [ 2 3 4 ] set difference members print").stdout
+    );
+}
+
+#[test]
+fn test_subset() {
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ 1 2 ] set [ 1 2 3 ] set subset? print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ 1 4 ] set [ 1 2 3 ] set subset? print").stdout
+    );
+}
+
+#[test]
+fn test_set_equality_and_type() {
+    assert_eq!(
+        "true",
+        &rail_oneliner("[ 1 2 ] set [ 2 1 1 ] set eq? print").stdout
+    );
+    assert_eq!("set", &rail_oneliner("[ ] set type print").stdout);
+}
+
+#[test]
+fn test_members_are_ordered_by_value() {
+    assert_eq!(
+        "[ 2 9 10 ]",
+        &rail_oneliner("[ 10 2 9 ] set members print").stdout
+    );
+    assert_eq!(
+        "1",
+        &rail_oneliner("[ 1 1.0 ] set members len print").stdout
+    );
+}
+
+#[test]
+fn test_members_of_different_types_stay_apart() {
+    assert_eq!(
+        "2",
+        &rail_oneliner("[ [ 1 ] ] set [ 1 ] set add members len print").stdout
+    );
+    assert_eq!(
+        "false",
+        &rail_oneliner("[ ] set stab add [ ] member? print").stdout
+    );
+    assert_eq!(
+        "2",
+        &rail_oneliner("[ ] set [ 1 ] [ ] generator add [ 1 ] [ ] generator add members len print")
+            .stdout
+    );
+}
@@ -0,0 +1,122 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+fn round_trips(value: &str) {
+    let res = rail_oneliner(&format!("{} dup to-source eval eq? print", value));
+    assert_eq!(
+        "true", &res.stdout,
+        "{} did not round trip: {}",
+        value, res.stderr
+    );
+}
+
+#[test]
+fn test_to_source_literals() {
+    assert_eq!(
+        "[ 1 2.5 3.0 true \"hi\" + [ ] ]",
+        &rail_oneliner("[ 1 2.5 3.0 true \"hi\" + [ ] ] to-source print").stdout
+    );
+}
+
+#[test]
+fn test_to_source_escapes_strings() {
+    assert_eq!(
+        "\"say \\\"hi\\\"\\\\\"",
+        &rail_oneliner("\"say \\\"hi\\\"\\\\\" to-source print").stdout
+    );
+}
+
+#[test]
+fn test_round_trip_scalars() {
+    round_trips("true");
+    round_trips("42");
+    round_trips("0 7 -");
+    round_trips("3.0");
+    round_trips("0.125");
+    round_trips("none");
+}
+
+#[test]
+fn test_round_trip_strings() {
+    round_trips("\"\"");
+    round_trips("\"say \\\"hi\\\"\"");
+    round_trips("\"back\\\\slash\"");
+    round_trips("\"two\\nlines\\tand a tab\"");
+}
+
+#[test]
+fn test_round_trip_commands() {
+    round_trips("[ + ] pop swap drop");
+    round_trips("[ [ swap drop ] [ 1 2 ] ]");
+}
+
+#[test]
+fn test_round_trip_collections() {
+    round_trips("stab");
+    round_trips("stab [ \"a\" 1 ] insert [ \"b\" [ 2 3 ] ] insert");
+    round_trips("stab [ \"say \\\"hi\\\"\" 1 ] insert");
+    round_trips("[ 1 2 2 3 ] set");
+    round_trips("dtab \"x\" 1 dtab-set \"x\" 2 dtab-set \"y\" 3 dtab-set");
+    round_trips("[ stab none [ 1.5 ] ]");
+}
+
+#[test]
+fn test_round_trip_records() {
+    let res =
+        rail_oneliner("[ x y ] \"point\" record 1 [ 2 3 ] point dup to-source eval eq? print");
+    assert_eq!("true", &res.stdout);
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!("[ 1 2 + ]", &rail_oneliner("\"1 2 +\" parse print").stdout);
+}
+
+#[test]
+fn test_parse_does_not_run() {
+    assert_eq!(
+        "[ nonsense 1 2 ]",
+        &rail_oneliner("\"nonsense 1 2\" parse print").stdout
+    );
+}
+
+#[test]
+fn test_parse_unbalanced() {
+    let res = rail_oneliner("\"[ 1 2\" parse");
+    assert!(
+        res.stderr
+            .contains("The [ at line 1, column 1 is never closed"),
+        "{}",
+        res.stderr
+    );
+
+    let res = rail_oneliner("\"1 2 ]\" parse");
+    assert!(
+        res.stderr
+            .contains("The ] at line 1, column 5 has no matching ["),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_eval() {
+    assert_eq!("7", &rail_oneliner("3 \"4 +\" eval print").stdout);
+}
+
+#[test]
+fn test_eval_keeps_definitions() {
+    assert_eq!(
+        "10",
+        &rail_oneliner("\"[ 2 * ] [ double ] def\" eval 5 double print").stdout
+    );
+}
+
+#[test]
+fn test_to_source_infinity() {
+    assert_eq!(
+        "inf",
+        &rail_oneliner("1.0 0.0 / to-source eval print").stdout
+    );
+}
@@ -0,0 +1,85 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+const FRUITS: &str = r#"stab [ "pear" 3 ] insert [ "apple" 1 ] insert [ "fig" 2 ] insert"#;
+
+fn with_fruits(source: &str) -> String {
+    rail_oneliner(&format!("{} {}", FRUITS, source)).stdout
+}
+
+#[test]
+fn test_sorted_printing() {
+    assert_eq!(
+        r#"[ [ "apple" 1 ] [ "fig" 2 ] [ "pear" 3 ] ]"#,
+        with_fruits("print")
+    );
+}
+
+#[test]
+fn test_keys_values_entries() {
+    assert_eq!(r#"[ "apple" "fig" "pear" ]"#, with_fruits("keys print"));
+    assert_eq!("[ 1 2 3 ]", with_fruits("values print"));
+    assert_eq!(
+        r#"[ [ "apple" 1 ] [ "fig" 2 ] [ "pear" 3 ] ]"#,
+        with_fruits("entries print")
+    );
+    assert_eq!("true", with_fruits("dup entries from-entries eq? print"));
+}
+
+#[test]
+fn test_from_entries_keeps_the_last_duplicate() {
+    let res = rail_oneliner(r#"[ [ "a" 1 ] [ "b" 2 ] [ "a" 3 ] ] from-entries print"#);
+    assert_eq!(r#"[ [ "a" 3 ] [ "b" 2 ] ]"#, &res.stdout);
+}
+
+#[test]
+fn test_has_key_and_remove() {
+    assert_eq!("true", with_fruits(r#""fig" has-key? print"#));
+    assert_eq!("false", with_fruits(r#""kiwi" has-key? print"#));
+    assert_eq!(
+        r#"[ "apple" "pear" ]"#,
+        with_fruits(r#""fig" remove keys print"#)
+    );
+}
+
+#[test]
+fn test_merge_and_update() {
+    assert_eq!(
+        "[ 1 20 4 3 ]",
+        with_fruits(r#"stab [ "fig" 20 ] insert [ "kiwi" 4 ] insert merge values print"#)
+    );
+    assert_eq!(
+        "[ 1 12 3 ]",
+        with_fruits(r#""fig" [ 10 + ] update values print"#)
+    );
+}
+
+#[test]
+fn test_map_and_filter() {
+    assert_eq!("[ 2 4 6 ]", with_fruits("[ 2 * ] stab-map values print"));
+    assert_eq!(
+        r#"[ "apple" "pear" ]"#,
+        with_fruits("[ [ k v ] -> v odd? ] stab-filter keys print")
+    );
+}
+
+#[test]
+fn test_nested_paths() {
+    let source = r#"
+        stab [ "a" "b" "c" ] 42 set-in
+        dup [ "a" "b" "c" ] get-in println
+        [ "a" "x" ] get-in println
+    "#;
+
+    assert_eq!("42\nnone\n", rail_oneliner(source).stdout);
+}
+
+#[test]
+fn test_set_in_rejects_bad_paths() {
+    let res = rail_oneliner(r#"stab [ "a" 1 ] insert [ "a" "b" ] 2 set-in"#);
+    assert!(res.stderr.contains("Type mismatch"));
+
+    let res = rail_oneliner("stab [ ] 2 set-in");
+    assert!(res.stderr.contains("Empty collection"));
+}
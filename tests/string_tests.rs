@@ -0,0 +1,50 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+#[test]
+fn test_escaped_quotes() {
+    assert_eq!(
+        "say \"hi\" \\ bye",
+        &rail_oneliner(r#""say \"hi\" \\ bye" print"#).stdout
+    );
+}
+
+#[test]
+fn test_unicode_escape() {
+    assert_eq!("🚂", &rail_oneliner(r#""\u{1F682}" print"#).stdout);
+}
+
+#[test]
+fn test_raw_string() {
+    assert_eq!(r"C:\rail\n", &rail_oneliner(r#"r"C:\rail\n" print"#).stdout);
+}
+
+#[test]
+fn test_multi_line_string() {
+    let res = rail_oneliner("\"one\ntwo\" println");
+    assert_eq!("one\ntwo\n", &res.stdout);
+}
+
+#[test]
+fn test_unterminated_string() {
+    let res = rail_oneliner("1 \"abc print");
+    assert_eq!("", &res.stdout);
+    assert!(
+        res.stderr
+            .contains("Unterminated string at line 1, column 3"),
+        "{}",
+        res.stderr
+    );
+}
+
+#[test]
+fn test_parse_unterminated_string() {
+    let res = rail_oneliner("\"1 \\\"abc\" parse");
+    assert!(
+        res.stderr
+            .contains("Unterminated string at line 1, column 3"),
+        "{}",
+        res.stderr
+    );
+}
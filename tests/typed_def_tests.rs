@@ -0,0 +1,52 @@
+mod rail_runner;
+
+use rail_runner::rail_oneliner;
+
+const REPEAT: &str =
+    "[ [ s n ] -> \"\" [ s concat ] n times ] [ repeat ] [ string i64 -- string ] def-typed";
+
+#[test]
+fn test_typed_def_runs() {
+    assert_eq!(
+        "ababab",
+        &rail_oneliner(&format!("{} \"ab\" 3 repeat print", REPEAT)).stdout
+    );
+}
+
+#[test]
+fn test_typed_def_checks_inputs() {
+    let res = rail_oneliner(&format!("{} 3 \"ab\" repeat", REPEAT));
+    assert!(res.stderr.contains("Type mismatch"));
+}
+
+#[test]
+fn test_typed_def_checks_outputs() {
+    let res = rail_oneliner("[ drop 5 ] [ wrong ] [ i64 -- string ] def-typed 1 wrong");
+    assert!(res.stderr.contains("Type mismatch"));
+    let res = rail_oneliner("[ dup ] [ too-many ] [ i64 -- i64 ] def-typed 1 too-many");
+    assert!(res.stderr.contains("Type mismatch"));
+}
+
+#[test]
+fn test_typed_def_rejects_unknown_types() {
+    let res = rail_oneliner("[ 1 + ] [ inc ] [ i46 -- i64 ] def-typed [ inc ] def? print");
+    assert_eq!("false", &res.stdout);
+    assert_eq!(
+        "[Warn] The signature of inc names i46, which is not a type\n",
+        &res.stderr
+    );
+}
+
+#[test]
+fn test_typed_def_underflow() {
+    let res = rail_oneliner(&format!("{} 3 repeat", REPEAT));
+    assert!(res.stderr.contains("Stack underflow"));
+}
+
+#[test]
+fn test_describe_shows_signature() {
+    assert_eq!(
+        "[ string i64 -- string ] Undocumented.",
+        &rail_oneliner(&format!("{} [ repeat ] describe print", REPEAT)).stdout
+    );
+}